@@ -1,6 +1,6 @@
 extern crate regex;
 extern crate risc_vm;
-use risc_vm::{DISCS, REGISTERS};
+use risc_vm::{MachineConfig, DISC_INSTRUCTIONS};
 use regex::{RegexBuilder, Regex};
 
 fn reg(s: &str) -> Regex {
@@ -32,9 +32,17 @@ lazy_static!{
     static ref VR: Regex = reg(r"(\w+) @(\w+)"); // Block Four
 
     static ref DV: Regex = reg(r"(\w+) :(\w+)");  // Block
-    static ref DR: Regex = reg(r"(\w+) :(\w+)"); //  Five
+    static ref DR: Regex = reg(r"@(\w+) :(\w+)"); //  Five
+
+    static ref R: Regex = reg(r"@(\w+)"); // Block Six
+
+    static ref V: Regex = reg(r"^(\w+)"); // Block Seven
+
+    static ref SV: Regex = reg(r"(\w+) :@(\w+)");  // Block
+    static ref SR: Regex = reg(r"@(\w+) :@(\w+)"); //  Eight
 }
 
+#[allow(clippy::upper_case_acronyms)]
 enum Operand {
     DVV(u8, u8, u8),
     DVR(u8, u8, u8),
@@ -49,40 +57,51 @@ enum Operand {
     DV(u8, u8),
     DR(u8, u8),
 
+    SV(u8, u8),
+    SR(u8, u8),
+
     RV(u8, u8),
     RR(u8, u8),
 
     VR(u8, u8),
+
+    R(u8),
+
+    V(u8),
 }
 
 impl Operand {
-    pub fn check(self) -> Result<Operand, String> {
+    pub fn check(self, config: &MachineConfig) -> Result<Operand, String> {
         use self::Operand::*;
 
+        let disc = |d: u8| (d as usize) < config.discs;
+        let reg = |r: u8| (r as usize) < config.registers;
+        let embedded = |x: u8| x < 8; //triples only have three bits for their first operand
+
         match self {
             DVV(a, b, c) => {
-                if (a as usize) < DISCS {
+                if disc(a) && embedded(a) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, {}, or {} are too big", a, b, c))
                 }
             },
             DVR(a, b, c) => {
-                if (a as usize) < DISCS && (c as usize) < REGISTERS {
+                if disc(a) && embedded(a) && reg(c) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, {}, or @{} are too big", a, b, c))
                 }
             },
             DRV(a, b, c) => {
-                if (a as usize) < DISCS && (b as usize) < REGISTERS {
+                if disc(a) && embedded(a) && reg(b) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, @{}, or {} are too big", a, b, c))
                 }
             },
             DRR(a, b, c) => {
-                if (a as usize) < DISCS && (b as usize) < REGISTERS && (c as usize) < REGISTERS {
+                if disc(a) && embedded(a) && reg(b) && reg(c) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, @{}, or @{} are too big", a, b, c))
@@ -90,28 +109,28 @@ impl Operand {
             },
 
             RVV(a, b, c) => {
-                if (a as usize) < REGISTERS {
+                if reg(a) && embedded(a) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, {}, or {} are too big", a, b, c))
                 }
             },
             RVR(a, b, c) => {
-                if (a as usize) < REGISTERS && (c as usize) < REGISTERS {
+                if reg(a) && embedded(a) && reg(c) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, {}, or @{} are too big", a, b, c))
                 }
             },
             RRV(a, b, c) => {
-                if (a as usize) < REGISTERS && (b as usize) < REGISTERS {
+                if reg(a) && embedded(a) && reg(b) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, @{}, or {} are too big", a, b, c))
                 }
             },
             RRR(a, b, c) => {
-                if (a as usize) < REGISTERS && (b as usize) < REGISTERS && (c as usize) < REGISTERS {
+                if reg(a) && embedded(a) && reg(b) && reg(c) {
                     Ok(self)
                 } else {
                     Err(format!(":{}, @{}, or @{} are too big", a, b, c))
@@ -119,22 +138,38 @@ impl Operand {
             },
 
             RV(a, b) => {
-                if (a as usize) < REGISTERS {
+                if reg(a) {
                     Ok(self)
                 } else {
                     Err(format!("@{} or {} are too big", a, b))
                 }
             },
             RR(a, b) =>{
-                if (a as usize) < REGISTERS && (b as usize) < REGISTERS {
+                if reg(a) && reg(b) {
                     Ok(self)
                 } else {
                     Err(format!("@{} or {} are too big", a, b))
                 }
             },
 
+            R(a) => {
+                if reg(a) {
+                    Ok(self)
+                } else {
+                    Err(format!("@{} is too big", a))
+                }
+            },
+
+            V(a) => { //only END takes a lone value
+                if a <= crate::MAX_STATUS {
+                    Ok(self)
+                } else {
+                    Err(format!("{} is too big, exit statuses go up to {}", a, crate::MAX_STATUS))
+                }
+            },
+
             VR(a, b) =>{
-                if (b as usize) < REGISTERS {
+                if reg(b) {
                     Ok(self)
                 } else {
                     Err(format!("@{} or {} are too big", a, b))
@@ -142,25 +177,48 @@ impl Operand {
             },
 
             DV(a, b) => {
-                if (a as usize) < DISCS {
+                if disc(b) {
                     Ok(self)
                 } else {
                     Err(format!("@{} or {} are too big", a, b))
                 }
             },
             DR(a, b) => {
-                if (a as usize) < DISCS && (b as usize) < REGISTERS {
+                if reg(a) && disc(b) {
                     Ok(self)
                 } else {
                     Err(format!("@{} or {} are too big", a, b))
                 }
             },
+
+            SV(a, b) => {
+                if reg(b) {
+                    Ok(self)
+                } else {
+                    Err(format!("{} or :@{} are too big", a, b))
+                }
+            },
+            SR(a, b) => {
+                if reg(a) && reg(b) {
+                    Ok(self)
+                } else {
+                    Err(format!("@{} or :@{} are too big", a, b))
+                }
+            },
         }
     }
 
     pub fn from_vec(v: &[u8], code: &str) -> Option<Operand> {
         use self::Operand::*;
 
+        if code == "R" {
+            return v.first().map(|&a| R(a));
+        }
+
+        if code == "V" {
+            return v.first().map(|&a| V(a));
+        }
+
         if v.len() < 2 {
             return None;
         }
@@ -174,6 +232,9 @@ impl Operand {
             "DV" => DV(v[0], v[1]),
             "DR" => DR(v[0], v[1]),
 
+            "SV" => SV(v[0], v[1]),
+            "SR" => SR(v[0], v[1]),
+
             _ => return Operand::from_vec_internal(v, code),
         })
     }
@@ -217,7 +278,14 @@ impl Operand {
             "DV" => DV.captures(s),
             "DR" => DR.captures(s),
 
+            "SV" => SV.captures(s),
+            "SR" => SR.captures(s),
+
             "VR" => VR.captures(s),
+
+            "R" => R.captures(s),
+
+            "V" => V.captures(s),
             _ => return None,
         };
 
@@ -257,7 +325,7 @@ impl Operand {
 
     pub fn from_codes(s: &str, codes: &[&str]) -> Option<Operand> {
         for code in codes.iter() {
-            if let Some(x) = Operand::parse_captures(&s, code) {
+            if let Some(x) = Operand::parse_captures(s, code) {
                 return Some(x);
             } else {
                 continue;
@@ -290,7 +358,41 @@ fn op_codes(ins: &str) -> Option<Vec<&str>> {
 
         "JZ" => vec!["DRV", "DRR", "RRV", "RRR"],
         "JNZ" => vec!["DRV", "DRR", "RRV", "RRR"],
-        "JMP" => vec!["DR", "DV", "RR", "RV"],
+        "JMP" => vec!["SR", "SV", "DR", "DV", "RR", "RV"], //RR and RV are the old spellings of SR and SV
+
+        "ADC" => vec!["RV", "RR"],
+        "SBC" => vec!["RV", "RR"],
+
+        "JC" => vec!["DV"],
+        "JNC" => vec!["DV"],
+        "JV" => vec!["DV"],
+        "JNV" => vec!["DV"],
+        "JN" => vec!["DV"],
+        "JNN" => vec!["DV"],
+        "JE" => vec!["DV"],
+        "JNE" => vec!["DV"],
+
+        "CALL" => vec!["DR", "DV"],
+        "PUSH" => vec!["R"],
+        "POP" => vec!["R"],
+
+        "SHL" => vec!["RV", "RR"],
+        "SHR" => vec!["RV", "RR"],
+        "ROL" => vec!["RV", "RR"],
+        "ROR" => vec!["RV", "RR"],
+        "BTST" => vec!["RV", "RR"],
+        "BSET" => vec!["RV", "RR"],
+        "BCLR" => vec!["RV", "RR"],
+        "POPC" => vec!["R"],
+
+        "MUL" => vec!["RV", "RR"],
+        "DIV" => vec!["RV", "RR", "VR"],
+        "MOD" => vec!["RV", "RR", "VR"],
+
+        "TRAP" => vec!["DV"],
+        "TLOC" => vec!["RR"],
+
+        "END" => vec!["V"],
         _ => return None,
     })
 }
@@ -436,8 +538,192 @@ fn partial_code(ins: &str, code: &str) -> Result<u8, String> {
             match code {
                 "DV" => 0b10000011,
                 "DR" => 0b10000100,
-                "RV" => 0b10000101,
-                "RR" => 0b10000110,
+                "SV" | "RV" => 0b10000101,
+                "SR" | "RR" => 0b10000110,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "ADC" => {
+            match code {
+                "RV" => 0b11001000,
+                "RR" => 0b11001001,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "SBC" => {
+            match code {
+                "RV" => 0b11001010,
+                "RR" => 0b11001011,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "JC" => {
+            match code {
+                "DV" => 0b11001100,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JNC" => {
+            match code {
+                "DV" => 0b11001101,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JV" => {
+            match code {
+                "DV" => 0b11001110,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JNV" => {
+            match code {
+                "DV" => 0b11001111,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JN" => {
+            match code {
+                "DV" => 0b11010000,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JNN" => {
+            match code {
+                "DV" => 0b11010001,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JE" => {
+            match code {
+                "DV" => 0b11010010,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "JNE" => {
+            match code {
+                "DV" => 0b11010011,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "CALL" => {
+            match code {
+                "DV" => 0b11010100,
+                "DR" => 0b11010101,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "PUSH" => {
+            match code {
+                "R" => 0b11010111,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "POP" => {
+            match code {
+                "R" => 0b11011000,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "SHL" => {
+            match code {
+                "RV" => 0b11011001,
+                "RR" => 0b11011010,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "SHR" => {
+            match code {
+                "RV" => 0b11011011,
+                "RR" => 0b11011100,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "ROL" => {
+            match code {
+                "RV" => 0b11011101,
+                "RR" => 0b11011110,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "ROR" => {
+            match code {
+                "RV" => 0b11011111,
+                "RR" => 0b11100000,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "BTST" => {
+            match code {
+                "RV" => 0b11100001,
+                "RR" => 0b11100010,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "BSET" => {
+            match code {
+                "RV" => 0b11100011,
+                "RR" => 0b11100100,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "BCLR" => {
+            match code {
+                "RV" => 0b11100101,
+                "RR" => 0b11100110,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "POPC" => {
+            match code {
+                "R" => 0b11100111,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "MUL" => {
+            match code {
+                "RV" => 0b11101000,
+                "RR" => 0b11101001,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "DIV" => {
+            match code {
+                "RV" => 0b11101010,
+                "VR" => 0b11101011,
+                "RR" => 0b11101100,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "MOD" => {
+            match code {
+                "RV" => 0b11101101,
+                "VR" => 0b11101110,
+                "RR" => 0b11101111,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "TRAP" => {
+            match code {
+                "DV" => 0b11110001,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+        "TLOC" => {
+            match code {
+                "RR" => 0b11110010,
+                _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
+            }
+        },
+
+        "END" => {
+            match code {
+                "V" => 0b00000000,
                 _ => return Err(format!("operand type {} cannot be used with {}", code, ins)),
             }
         },
@@ -462,10 +748,17 @@ fn ins_code(ins: &str, ops: Operand) -> Result<[u8; 3], String> {
         DV(a, b) => (None, a, b, "DV"),
         DR(a, b) => (None, a, b, "DR"),
 
+        SV(a, b) => (None, a, b, "SV"),
+        SR(a, b) => (None, a, b, "SR"),
+
         RV(a, b) => (None, a, b, "RV"),
         RR(a, b) => (None, a, b, "RR"),
 
         VR(a, b) => (None, a, b, "VR"),
+
+        R(a) => (None, a, 0, "R"),
+
+        V(a) => (None, a, 0, "V"),
     };
 
     let ins_code = partial_code(ins, code)?;
@@ -476,7 +769,7 @@ fn ins_code(ins: &str, ops: Operand) -> Result<[u8; 3], String> {
     }, a, b])
 }
 
-fn parse_instruction(ins: &str, op: &str) -> Result<[u8; 3], String> {
+fn parse_instruction(ins: &str, op: &str, config: &MachineConfig) -> Result<[u8; 3], String> {
     let codes = match op_codes(ins) {
         Some(x) => x,
         None => return Err(format!("Could not recognise instruction {:?}", ins))
@@ -487,21 +780,25 @@ fn parse_instruction(ins: &str, op: &str) -> Result<[u8; 3], String> {
         None => return Err(format!("Could not compile operands {:?}", op)),
     };
 
-    ins_code(ins, ops.check()?)
+    ins_code(ins, ops.check(config)?)
 }
 
-fn line(ln: &str) -> Result<[u8; 3], String> {
+fn line(ln: &str, config: &MachineConfig) -> Result<[u8; 3], String> {
     if ln == "END" {
         Ok([0, 0, 0])
+    } else if ln == "RET" {
+        Ok([0b11010110, 0, 0])
+    } else if ln == "RETT" {
+        Ok([0b11110000, 0, 0])
     } else {
         match INS.captures(ln) {
-            Some(x) => parse_instruction(&x[1], &x[2]),
+            Some(x) => parse_instruction(&x[1], &x[2], config),
             None => Err(format!("Could not recognise instruction {:?}", ln))
         }
     }
 }
 
-pub fn rasm(path: &str) -> Result<Vec<u8>, String> {
+pub fn rasm(path: &str, config: &MachineConfig) -> Result<Vec<u8>, String> { //operands are checked against the machine the program is for
     let content = match std::fs::read_to_string(path) {
         Ok(x) => x,
         Err(x) => return Err(format!("{}", x)),
@@ -514,6 +811,10 @@ pub fn rasm(path: &str) -> Result<Vec<u8>, String> {
         })
         .collect();
 
+    if discs.len() > config.program_discs.len() {
+        return Err(format!("Program has {} discs, but the machine only loads {}.", discs.len(), config.program_discs.len()));
+    }
+
     let mut program = vec![];
 
     for (disc_index, d) in discs.iter().enumerate() {
@@ -522,13 +823,16 @@ pub fn rasm(path: &str) -> Result<Vec<u8>, String> {
             if COMMENT.is_match(i) || WHITESPACE.is_match(i) {
                 continue;
             }
-            let iterator = match line(i) {
+            let iterator = match line(i, config) {
                 Ok(x) => x,
                 Err(err) => return Err(format!("Errored at `{}` on disc {} at line {}.", err, disc_index, ins_index)),
             };
 
             disc.extend(iterator.iter());
         }
+        if disc.len() > DISC_INSTRUCTIONS*3 {
+            return Err(format!("Disc {} has {} instructions, but only {} fit on a disc.", disc_index, disc.len()/3, DISC_INSTRUCTIONS));
+        }
         program.append(&mut disc);
         program.push(0b11111111);
     }
@@ -0,0 +1,95 @@
+use std::{error, fmt};
+use crate::{ReadMode, DISCS, REGISTERS, CONSOLE_DISC, STACK_DISC};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardDevice { //devices `boot` knows how to build and attach
+    Console, //writes to stdout
+    Input(ReadMode), //reads from stdin
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineConfig { //the shape of a machine; the default is the standard 8 disc, 8 register one
+    pub discs: usize,
+    pub registers: usize,
+    pub program_discs: Vec<u8>, //discs a program is loaded onto, in order; by default every one without a device or the stack
+    pub devices: Vec<(u8, StandardDevice)>,
+    pub stack_disc: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    Discs(usize), //disc count out of range
+    Registers(usize), //register count out of range
+    NoDisc(u8), //refers to a disc the machine doesn't have
+    SharedDisc(u8), //two devices on one disc
+    MappedTwice(u8), //the same disc appears in program_discs more than once
+    MappedOver(u8), //a program disc that also has a device or the stack on it
+}
+
+impl Default for MachineConfig {
+    fn default() -> MachineConfig {
+        let mut config = MachineConfig {
+            discs: DISCS,
+            registers: REGISTERS,
+            program_discs: vec![],
+            devices: vec![(CONSOLE_DISC, StandardDevice::Console)], //no input, so disc 5 stays plain memory for programs that use it as such
+            stack_disc: STACK_DISC,
+        };
+        config.program_discs = config.free_discs();
+        config
+    }
+}
+
+impl MachineConfig {
+    pub fn free_discs(&self) -> Vec<u8> { //every disc without a device or the stack on it, in order
+        (0..self.discs.min(255) as u8)
+            .filter(|&d| d != self.stack_disc && !self.devices.iter().any(|&(e, _)| e == d))
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(1..=255).contains(&self.discs) { //counts are stored in a byte in snapshots
+            return Err(ConfigError::Discs(self.discs));
+        }
+        if !(2..=255).contains(&self.registers) { //MUL needs @0 and @1
+            return Err(ConfigError::Registers(self.registers));
+        }
+        let discs = self.program_discs.iter()
+            .chain(self.devices.iter().map(|(d, _)| d))
+            .chain(Some(&self.stack_disc));
+        for &d in discs {
+            if d as usize >= self.discs {
+                return Err(ConfigError::NoDisc(d));
+            }
+        }
+        for (i, (d, _)) in self.devices.iter().enumerate() {
+            if self.devices[..i].iter().any(|(e, _)| e == d) {
+                return Err(ConfigError::SharedDisc(*d));
+            }
+        }
+        for (i, &d) in self.program_discs.iter().enumerate() {
+            if self.program_discs[..i].contains(&d) {
+                return Err(ConfigError::MappedTwice(d));
+            }
+            if d == self.stack_disc || self.devices.iter().any(|&(e, _)| e == d) {
+                return Err(ConfigError::MappedOver(d));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Discs(n) => write!(f, "A machine needs between 1 and 255 discs, not {}.", n),
+            ConfigError::Registers(n) => write!(f, "A machine needs between 2 and 255 registers, not {}.", n),
+            ConfigError::NoDisc(d) => write!(f, "Disc :{} does not exist on this machine.", d),
+            ConfigError::SharedDisc(d) => write!(f, "Disc :{} has more than one device on it.", d),
+            ConfigError::MappedTwice(d) => write!(f, "Disc :{} is in the program map more than once.", d),
+            ConfigError::MappedOver(d) => write!(f, "Disc :{} can't hold a program, it has a device or the stack on it.", d),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute, //fetching an instruction; watchpoints never see these
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Access::Read => write!(f, "read"),
+            Access::Write => write!(f, "write"),
+            Access::Execute => write!(f, "execute"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWatch { //a range of indices on a disc, and which accesses to them stop the machine
+    pub disc: u8,
+    pub from: u8,
+    pub to: u8, //inclusive
+    pub read: bool,
+    pub write: bool,
+}
+
+impl MemoryWatch {
+    pub fn covers(&self, d: u8, i: u8, access: Access) -> bool {
+        let wanted = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => false,
+        };
+        wanted && d == self.disc && self.from <= i && i <= self.to
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit { //what tripped a watchpoint
+    Memory { disc: u8, index: u8, access: Access, value: u8 }, //value is what was read or written
+    Register { register: u8, old: u8, new: u8 },
+}
+
+impl fmt::Display for Hit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hit::Memory { disc, index, access: Access::Read, value } => write!(f, "read {} from :{} {}", value, disc, index),
+            Hit::Memory { disc, index, access: Access::Write, value } => write!(f, "wrote {} to :{} {}", value, disc, index),
+            Hit::Memory { disc, index, access: Access::Execute, .. } => write!(f, "executed :{} {}", disc, index),
+            Hit::Register { register, old, new } => write!(f, "@{} changed from {} to {}", register, old, new),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfModify { //what to do when a program writes over bytes it has run as instructions
+    #[default]
+    Allow, //nothing, and don't keep track
+    Report, //note it in `Machine::code_writes`
+    Fault, //stop with CodeWritten
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeWrite { //an instruction writing over one that has already run
+    pub writer: (u8, u8), //(disc, pc) of the instruction doing the write
+    pub target: (u8, u8), //(disc, index) written to
+    pub instruction: (u8, u8), //(disc, pc) of the instruction the target byte belongs to
+}
+
+impl fmt::Display for CodeWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "instruction at :{} {} wrote :{} {}, part of the instruction at :{} {}",
+            self.writer.0, self.writer.1, self.target.0, self.target.1, self.instruction.0, self.instruction.1)
+    }
+}
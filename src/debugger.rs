@@ -0,0 +1,259 @@
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use risc_vm::{decode, Machine, MemoryWatch, Stop};
+
+const HELP: &str = "\
+step [n]               execute n instructions (default 1)
+next                   run until the instruction after this one
+continue               run until the program ends or something stops it
+back [n]               undo n instructions (default 1)
+rewind <steps>         undo until only that many instructions (not cycles) have run
+rewind cycles <n>      undo until no more than n cycles have run
+lastwrite @<r> | :<disc> <i>
+                       undo back to the last instruction that wrote there
+regs                   show registers and position
+mem <disc> <from> [to] dump memory
+break [<disc> <pc>]    set a breakpoint, or list them
+delete <disc> <pc>     remove a breakpoint
+watch @<r>             stop when a register changes
+watch :<disc> <from> [to] [r|w|rw]
+                       stop when memory is read and/or written
+unwatch @<r> | :<disc> <i>
+disasm [n]             disassemble n instructions either side of pc (default 4)
+set @<r> <v>           set a register
+set :<disc> <i> <v>    set a byte of memory
+quit";
+
+fn num(s: Option<&&str>) -> Result<u8, String> {
+    match s {
+        Some(x) => x.trim_start_matches(['@', ':']).parse().map_err(|_| format!("{:?} is not a byte", x)),
+        None => Err(String::from("missing argument")),
+    }
+}
+
+fn disasm_at(vm: &Machine, d: u8, pc: u8) -> String {
+    let ins = match vm.instruction_at(d, pc) {
+        Ok(x) => x,
+        Err(_) => return String::from("??"),
+    };
+    match decode(ins) {
+        Ok(x) => format!("{:08b} {:08b} {:08b}  {}", ins[0], ins[1], ins[2], x),
+        Err(err) => format!("{:08b} {:08b} {:08b}  ({})", ins[0], ins[1], ins[2], err),
+    }
+}
+
+fn show_position(vm: &Machine) {
+    if vm.is_past_end() {
+        println!("  :{} {:>3}  (ran off the end of the disc)", vm.dsccount(), vm.prgcount());
+        return;
+    }
+    println!("  :{} {:>3}  {}", vm.dsccount(), vm.prgcount(), disasm_at(vm, vm.dsccount(), vm.prgcount()));
+}
+
+fn show_stop(vm: &Machine, stop: Option<Stop>) {
+    if let Some(stop) = stop {
+        println!("Stopped: {}.", stop);
+    }
+    if vm.is_active() {
+        show_position(vm);
+    }
+}
+
+fn command(vm: &mut Machine, words: &[&str]) -> Result<bool, String> { //false once the user is done
+    match words[0] {
+        "step" | "s" => {
+            let n = if words.len() > 1 {num(words.get(1))?} else {1};
+            let mut stop = None;
+            for _ in 0..n {
+                stop = vm.step().map_err(|err| err.to_string())?;
+                if stop.is_some() {
+                    break;
+                }
+            }
+            show_stop(vm, stop);
+        },
+        "next" | "n" => { //a temporary breakpoint on the following instruction
+            let (d, pc) = vm.locate(vm.dsccount(), vm.prgcount(), 3).map_err(|f| f.to_string())?;
+            let existed = vm.breakpoints().contains(&(d, pc));
+            vm.add_breakpoint(d, pc);
+            let stop = vm.run();
+            if !existed {
+                vm.remove_breakpoint(d, pc);
+            }
+            match stop.map_err(|err| err.to_string())? {
+                Stop::Breakpoint { disc, pc: p } if !existed && (disc, p) == (d, pc) => show_stop(vm, None),
+                stop => show_stop(vm, Some(stop)),
+            }
+        },
+        "continue" | "c" => {
+            let stop = vm.run().map_err(|err| err.to_string())?;
+            show_stop(vm, Some(stop));
+        },
+        "back" => {
+            let n = if words.len() > 1 {num(words.get(1))?} else {1};
+            for _ in 0..n {
+                if !vm.step_back() {
+                    println!("Journal exhausted.");
+                    break;
+                }
+            }
+            show_position(vm);
+        },
+        "rewind" if words.get(1) == Some(&"cycles") => {
+            let cycles = words.get(2).ok_or("missing argument")?.parse().map_err(|_| "cycles must be a number")?;
+            if !vm.rewind_to_cycle(cycles) {
+                return Err(format!("can only rewind to between {} and {} cycles", vm.oldest_cycle(), vm.cycles()));
+            }
+            show_position(vm);
+        },
+        "rewind" => {
+            let steps = words.get(1).ok_or("missing argument")?.parse().map_err(|_| "steps must be a number")?;
+            if !vm.rewind_to(steps) {
+                return Err(format!("can only rewind to between {} and {} instructions", vm.oldest_step(), vm.steps()));
+            }
+            show_position(vm);
+        },
+        "lastwrite" => {
+            let target = words.get(1).ok_or("missing argument")?;
+            let found = if target.starts_with('@') {
+                vm.back_to_register_write(num(words.get(1))?)
+            } else {
+                vm.back_to_memory_write(num(words.get(1))?, num(words.get(2))?)
+            };
+            if !found {
+                return Err(String::from("no write to that in the journal"));
+            }
+            show_position(vm);
+        },
+        "regs" | "r" => {
+            for (i, r) in vm.registers().iter().enumerate() {
+                print!("@{}={:<4}", i, r);
+            }
+            println!();
+            let flags: String = "ZCVN".chars().enumerate()
+                .map(|(i, c)| if vm.flags() & (1 << i) != 0 {c} else {'-'})
+                .collect();
+            println!("flags {}, sp :{} {}, disc {}, pc {}, {} instructions, {} cycles{}", flags, vm.stack_disc(), vm.sp(), vm.dsccount(), vm.prgcount(), vm.steps(), vm.cycles(), if vm.is_active() {""} else {" (ended)"});
+        },
+        "mem" | "m" => {
+            let d = num(words.get(1))?;
+            let from = num(words.get(2))?;
+            let to = if words.len() > 3 {num(words.get(3))?} else {from};
+            let disc = vm.disc(d).ok_or(format!("disc {} does not exist", d))?;
+            for row in (from as usize..=to as usize).collect::<Vec<_>>().chunks(8) {
+                print!("  :{} {:>3} ", d, row[0]);
+                for i in row {
+                    print!(" {:08b}", disc[*i]);
+                }
+                println!();
+            }
+        },
+        "break" | "b" => {
+            if words.len() == 1 {
+                for (d, pc) in vm.breakpoints() {
+                    println!("  :{} {}", d, pc);
+                }
+            } else {
+                vm.add_breakpoint(num(words.get(1))?, num(words.get(2))?);
+            }
+        },
+        "delete" | "d" => {
+            if !vm.remove_breakpoint(num(words.get(1))?, num(words.get(2))?) {
+                return Err(String::from("no such breakpoint"));
+            }
+        },
+        "watch" | "w" => {
+            let target = words.get(1).ok_or("missing argument")?;
+            if target.starts_with('@') {
+                vm.watch_register(num(words.get(1))?);
+            } else {
+                let disc = num(words.get(1))?;
+                let from = num(words.get(2))?;
+                let to = match words.get(3) {
+                    Some(x) if x.parse::<u8>().is_ok() => num(words.get(3))?,
+                    _ => from,
+                };
+                let mode = words.iter().skip(3).find(|x| x.parse::<u8>().is_err()).unwrap_or(&"w");
+                let (read, write) = match *mode {
+                    "r" => (true, false),
+                    "w" => (false, true),
+                    "rw" => (true, true),
+                    x => return Err(format!("{:?} is not r, w or rw", x)),
+                };
+                vm.watch_memory(MemoryWatch { disc, from, to, read, write });
+            }
+        },
+        "unwatch" => {
+            let target = words.get(1).ok_or("missing argument")?;
+            let removed = if target.starts_with('@') {
+                vm.unwatch_register(num(words.get(1))?)
+            } else {
+                vm.unwatch_memory(num(words.get(1))?, num(words.get(2))?)
+            };
+            if !removed {
+                return Err(String::from("no such watchpoint"));
+            }
+        },
+        "disasm" | "x" => {
+            let n = if words.len() > 1 {num(words.get(1))? as i32} else {4};
+            let (d, pc) = (vm.dsccount(), vm.prgcount());
+            let mut at: Vec<(u8, u8)> = (1..=n).rev() //behind pc, within the disc
+                .filter_map(|k| u8::try_from(pc as i32 - k*3).ok().map(|i| (d, i)))
+                .collect();
+            let mut next = Ok((d, pc));
+            for _ in 0..=n { //ahead of pc, wherever the fetch policy goes
+                let Ok(x) = next else { break };
+                at.push(x);
+                next = vm.locate(x.0, x.1, 3);
+            }
+            for (d, i) in at {
+                let marker = if (d, i) == (vm.dsccount(), pc) {'>'} else if vm.breakpoints().contains(&(d, i)) {'*'} else {' '};
+                println!("{} :{} {:>3}  {}", marker, d, i, disasm_at(vm, d, i));
+            }
+        },
+        "set" => {
+            let target = words.get(1).ok_or("missing argument")?;
+            if target.starts_with('@') {
+                vm.set_register(num(words.get(1))?, num(words.get(2))?).map_err(|f| f.to_string())?;
+            } else {
+                vm.poke(num(words.get(1))?, num(words.get(2))?, num(words.get(3))?).map_err(|f| f.to_string())?;
+            }
+        },
+        "help" | "h" => println!("{}", HELP),
+        "quit" | "q" => return Ok(false),
+        x => return Err(format!("{:?} is not a command, try help", x)),
+    }
+    Ok(true)
+}
+
+const JOURNAL: usize = 100000; //instructions the debugger can step back over
+
+pub fn repl(mut vm: Machine) -> Machine { //hands the machine back once the user quits
+    vm.set_journal_limit(JOURNAL);
+    let stdin = io::stdin();
+    let mut last = String::new();
+    show_position(&vm);
+    loop {
+        print!("(rvm) ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {},
+        }
+        if line.trim().is_empty() { //repeat the last command, like gdb
+            line = last.clone();
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        match command(&mut vm, &words) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(err) => println!("Debugger ERROR: {}", err),
+        }
+        last = line.clone();
+    }
+    vm
+}
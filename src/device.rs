@@ -0,0 +1,183 @@
+use std::{fs, io, thread};
+use std::io::{Read, Write};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use crate::Fault;
+
+//devices are memory-mapped onto a disc: the program talks to them by
+//reading and writing that disc's bytes like any other memory
+pub trait Device {
+    fn on_read(&mut self, disc: &mut [u8; 256], i: u8) -> Result<(), Fault> { //called before the machine reads disc[i]
+        Ok(())
+    }
+
+    fn on_write(&mut self, disc: &mut [u8; 256], i: u8) -> Result<(), Fault> { //called after the machine writes disc[i]
+        Ok(())
+    }
+
+    fn tick(&mut self, disc: &mut [u8; 256]) -> Result<(), Fault> { //called after every instruction
+        Ok(())
+    }
+}
+
+pub struct Console { //index 0 holds a character, setting index 1 prints it
+    out: Box<dyn Write>,
+}
+
+impl Console {
+    pub fn new<W: Write + 'static>(out: W) -> Console {
+        Console { out: Box::new(out) }
+    }
+
+    pub fn stdout() -> Console {
+        Console::new(io::stdout())
+    }
+
+    pub fn capture() -> (Console, Capture) { //a console whose output can be read back afterwards
+        let capture = Capture::default();
+        (Console::new(capture.clone()), capture)
+    }
+}
+
+impl Device for Console {
+    fn tick(&mut self, disc: &mut [u8; 256]) -> Result<(), Fault> {
+        if disc[1] != 0 {
+            write!(self.out, "{}", disc[0] as char)
+                .and_then(|_| self.out.flush())
+                .map_err(|err| Fault::Io(err.to_string()))?;
+            disc[1] = 0;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Capture { //shared buffer collecting everything written to it
+    buf: Rc<RefCell<Vec<u8>>>,
+}
+
+impl Capture {
+    pub fn bytes(&self) -> Vec<u8> {
+        self.buf.borrow().clone()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.buf.borrow()).into_owned()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, b: &[u8]) -> io::Result<usize> {
+        self.buf.borrow_mut().extend_from_slice(b);
+        Ok(b.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadMode {
+    Blocking, //reading the port waits for the next byte
+    NonBlocking, //bytes arrive in the background; poll index 1
+}
+
+enum Source {
+    Reader(Box<dyn Read>), //read directly, on demand
+    Channel(Receiver<u8>), //fed by a background thread
+}
+
+pub struct Input { //index 0 holds a byte, index 1 is set while it is unread, index 2 is set once input runs out
+    source: Source,
+    mode: ReadMode,
+    eof: bool,
+}
+
+impl Input {
+    pub fn new<R: Read + 'static>(reader: R, mode: ReadMode) -> Input {
+        Input { source: Source::Reader(Box::new(reader)), mode, eof: false }
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>, mode: ReadMode) -> Input { //scripted input, mostly for tests
+        Input::new(io::Cursor::new(bytes), mode)
+    }
+
+    pub fn file(path: &str, mode: ReadMode) -> io::Result<Input> {
+        Ok(Input::new(fs::File::open(path)?, mode))
+    }
+
+    pub fn stdin(mode: ReadMode) -> Input {
+        match mode {
+            ReadMode::Blocking => Input::new(io::stdin(), mode),
+            ReadMode::NonBlocking => { //stdin can't be polled, so read it on another thread
+                let (tx, rx) = mpsc::channel();
+                thread::spawn(move || {
+                    for b in io::BufReader::new(io::stdin()).bytes() {
+                        match b {
+                            Ok(b) => if tx.send(b).is_err() {break},
+                            Err(_) => break,
+                        }
+                    }
+                });
+                Input { source: Source::Channel(rx), mode, eof: false }
+            },
+        }
+    }
+
+    fn next(&mut self, block: bool) -> Result<Option<u8>, Fault> { //next byte, or None if there isn't one (yet)
+        match &mut self.source {
+            Source::Reader(r) => {
+                let mut b = [0];
+                loop {
+                    match r.read(&mut b) {
+                        Ok(0) => {self.eof = true; return Ok(None)},
+                        Ok(_) => return Ok(Some(b[0])),
+                        Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(err) => return Err(Fault::Io(err.to_string())),
+                    }
+                }
+            },
+            Source::Channel(rx) => {
+                let b = if block {
+                    rx.recv().map_err(|_| TryRecvError::Disconnected)
+                } else {
+                    rx.try_recv()
+                };
+                match b {
+                    Ok(b) => Ok(Some(b)),
+                    Err(TryRecvError::Empty) => Ok(None),
+                    Err(TryRecvError::Disconnected) => {self.eof = true; Ok(None)},
+                }
+            },
+        }
+    }
+
+    fn fill(&mut self, disc: &mut [u8; 256], block: bool) -> Result<(), Fault> { //fetch a byte if the last one was taken
+        if disc[1] == 0 && !self.eof {
+            if let Some(b) = self.next(block)? {
+                disc[0] = b;
+                disc[1] = 1;
+            }
+        }
+        disc[2] = if self.eof && disc[1] == 0 {1} else {0};
+        Ok(())
+    }
+}
+
+impl Device for Input {
+    fn on_read(&mut self, disc: &mut [u8; 256], i: u8) -> Result<(), Fault> {
+        if i <= 1 {
+            self.fill(disc, self.mode == ReadMode::Blocking)?;
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self, disc: &mut [u8; 256]) -> Result<(), Fault> {
+        if self.mode == ReadMode::NonBlocking {
+            self.fill(disc, false)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::{error, fmt};
+use crate::isa::DecodeError;
+use crate::debug::{Access, CodeWrite};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault { //what went wrong, without saying where
+    BadRegister(u8), //register index out of range
+    BadDisc(u8), //disc index out of range
+    IllegalInstruction(u8), //opcode not defined by the instruction set
+    FetchPastEnd, //instruction would straddle the end of its disc
+    StackOverflow, //pushed onto a full stack
+    StackUnderflow, //popped from an empty stack
+    DivideByZero, //DIV or MOD by 0
+    AccessDenied { disc: u8, index: u8, access: Access }, //the disc's permissions don't allow it
+    CodeWritten(CodeWrite), //wrote over an instruction that has already run, with SelfModify::Fault
+    Io(String), //a device failed to talk to the host
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError { //why a program couldn't be put onto a machine, before it ever ran
+    DoesNotFit { sections: usize, discs: usize }, //a program image has more discs than it was given places to load them
+    NoDisc(u8), //loading onto, or attaching a device to, a disc the machine doesn't have
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError { //what `run` can fail with
+    Load(LoadError),
+    Fault(VmError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmError { //a fault, plus the machine state at the instruction that caused it
+    pub fault: Fault,
+    pub disc: u8,
+    pub pc: u8,
+    pub ins: [u8; 3],
+    pub registers: Vec<u8>,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::BadRegister(r) => write!(f, "Register @{} does not exist.", r),
+            Fault::BadDisc(d) => write!(f, "Disc :{} does not exist.", d),
+            Fault::IllegalInstruction(op) => write!(f, "Opcode {:08b} is not a valid instruction.", op),
+            Fault::FetchPastEnd => write!(f, "Instruction runs past the end of the disc."),
+            Fault::StackOverflow => write!(f, "Stack overflowed."),
+            Fault::StackUnderflow => write!(f, "Popped from an empty stack."),
+            Fault::DivideByZero => write!(f, "Divided by zero."),
+            Fault::AccessDenied { disc, index, access } => write!(f, "Disc :{} does not allow {} access (index {}).", disc, access, index),
+            Fault::CodeWritten(write) => write!(f, "Self-modifying code: {}.", write),
+            Fault::Io(err) => write!(f, "Device I/O failed: {}", err),
+        }
+    }
+}
+
+impl Fault {
+    pub fn code(&self) -> u8 { //cause code handed to trap handlers in @0
+        match self {
+            Fault::BadRegister(_) => 1,
+            Fault::BadDisc(_) => 2,
+            Fault::IllegalInstruction(_) => 3,
+            Fault::FetchPastEnd => 4,
+            Fault::StackOverflow => 5,
+            Fault::StackUnderflow => 6,
+            Fault::DivideByZero => 7,
+            Fault::Io(_) => 8,
+            Fault::AccessDenied { .. } => 9,
+            Fault::CodeWritten(_) => 10,
+        }
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (disc {}, pc {}, instruction {:08b} {:08b} {:08b})", self.fault, self.disc, self.pc, self.ins[0], self.ins[1], self.ins[2])
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::DoesNotFit { sections, discs } => write!(f, "Program has {} discs, but there are only {} to load it onto.", sections, discs),
+            LoadError::NoDisc(d) => write!(f, "Disc :{} does not exist.", d),
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Load(err) => write!(f, "{}", err),
+            RunError::Fault(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<LoadError> for RunError {
+    fn from(err: LoadError) -> RunError {
+        RunError::Load(err)
+    }
+}
+
+impl From<VmError> for RunError {
+    fn from(err: VmError) -> RunError {
+        RunError::Fault(err)
+    }
+}
+
+impl From<DecodeError> for Fault {
+    fn from(err: DecodeError) -> Fault {
+        match err {
+            DecodeError::UnknownOpcode(op) => Fault::IllegalInstruction(op),
+        }
+    }
+}
+
+impl error::Error for Fault {}
+
+impl error::Error for LoadError {}
+
+impl error::Error for RunError {}
+
+impl error::Error for VmError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.fault)
+    }
+}
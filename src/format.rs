@@ -1,8 +1,8 @@
 use std::{fs, process};
 
 fn force_len(mut v: Vec<u8>, l: usize) -> Vec<u8> { //force len of vec
-    if {v.len() == l} {v}
-    else if {v.len() > l} {v[0..l].to_vec()}
+    if v.len() == l {v}
+    else if v.len() > l {v[0..l].to_vec()}
     else {
         for i in 0..l-v.len() {
             v.push(0);
@@ -12,18 +12,26 @@ fn force_len(mut v: Vec<u8>, l: usize) -> Vec<u8> { //force len of vec
 }
 
 fn force_disc(v: Vec<u8>) -> [u8; 256] {
+    if v.len() > 256 {
+        println!("Format ERROR: A disc holds 256 bytes, not {}.", v.len());
+        process::exit(202);
+    }
     let mut arr = [0; 256];
     arr.copy_from_slice(&force_len(v, 256));
     arr
 }
 
 pub fn bytes(path: &String) -> Vec<[u8; 256]> { //read bytes from a file
-    fs::read(path)
+    let mut bytes = fs::read(path)
         .unwrap_or_else(|err| {
             println!("Argument ERROR: {}", err);
-            process::exit(1);
-        })
-        .split(|b| (b == &0b11111111))
+            process::exit(201);
+        });
+    if bytes.last() == Some(&0b11111111) { //the assembler ends every disc with a separator, not just the ones between
+        bytes.pop();
+    }
+    bytes
+        .split(|b| b == &0b11111111)
         .map(|d| {
             force_disc(d.to_vec())
         })
@@ -34,20 +42,20 @@ pub fn debug(path: &String) -> Vec<[u8; 256]> { //read a risc debug executable
     fn parse_byte(x: &str) -> u8 {
         u8::from_str_radix(x, 2).unwrap_or_else(|err| {
             println!("Format ERROR: Couldn't parse {:?} to byte.", x);
-            process::exit(2);
+            process::exit(202);
         })
     }
     fs::read_to_string(path)
         .unwrap_or_else(|err| {
             println!("Argument ERROR: {}", err);
-            process::exit(1);
+            process::exit(201);
         })
-        .split(|d| (d == '-'))
+        .split('-')
         .map(|d| {
             force_disc(
                 String::from(d)
-                    .split(|d| (d == ' ') || (d == '\n') || (d == '\r'))
-                    .filter(|d| (d.len() > 0))
+                    .split([' ', '\n', '\r'])
+                    .filter(|d| !d.is_empty())
                     .map(parse_byte)
                     .collect()
             )
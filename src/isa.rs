@@ -0,0 +1,234 @@
+use std::{error, fmt};
+
+//the instruction set, defined once:
+//every instruction is three bytes: an opcode and two operand bytes
+//`single` and `pair` instructions own a single opcode, taking one or two operands
+//`triple` instructions own eight opcodes, the low three bits being a third (embedded) operand
+//operand syntax is given in assembly order, triples starting with the embedded operand;
+//letters are V (value or index), R (register), D (disc) and S (disc held in a register);
+//last comes the cost in cycles
+macro_rules! isa {
+    (
+        none { $( $n0:ident = $c0:expr, $m0:expr, $y0:expr; )* }
+        single { $( $n1:ident = $c1:expr, $m1:expr, $s1:expr, $y1:expr; )* }
+        pair { $( $n2:ident = $c2:expr, $m2:expr, $s2:expr, $y2:expr; )* }
+        triple { $( $n3:ident = $c3:expr, $m3:expr, $s3:expr, $y3:expr; )* }
+    ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Instruction {
+            $( $n0, )*
+            $( $n1(u8), )*
+            $( $n2(u8, u8), )*
+            $( $n3(u8, u8, u8), )*
+        }
+
+        pub fn decode(ins: [u8; 3]) -> Result<Instruction, DecodeError> {
+            use self::Instruction::*;
+
+            $( if ins[0] == $c0 { return Ok($n0); } )*
+            $( if ins[0] == $c1 { return Ok($n1(ins[1])); } )*
+            $( if ins[0] == $c2 { return Ok($n2(ins[1], ins[2])); } )*
+            $( if ins[0] & 0b11111000 == $c3 { return Ok($n3(ins[1], ins[2], ins[0] & 0b00000111)); } )*
+            Err(DecodeError::UnknownOpcode(ins[0]))
+        }
+
+        pub fn encode(ins: Instruction) -> [u8; 3] { //a triple's embedded operand only has three bits: 8 or more is a bug in the caller, and is cut down to its low bits in release builds
+            use self::Instruction::*;
+
+            match ins {
+                $( $n0 => [$c0, 0, 0], )*
+                $( $n1(a) => [$c1, a, 0], )*
+                $( $n2(a, b) => [$c2, a, b], )*
+                $( $n3(a, b, c) => {
+                    debug_assert!(c < 8, "{} can only name discs and registers 0 to 7 in its last operand, not {}", $m3, c);
+                    [$c3 | (c & 0b00000111), a, b]
+                }, )*
+            }
+        }
+
+        impl Instruction {
+            pub fn mnemonic(&self) -> &'static str {
+                use self::Instruction::*;
+
+                match self {
+                    $( $n0 => $m0, )*
+                    $( $n1(..) => $m1, )*
+                    $( $n2(..) => $m2, )*
+                    $( $n3(..) => $m3, )*
+                }
+            }
+
+            pub fn syntax(&self) -> &'static str { //operand letters, in assembly order
+                use self::Instruction::*;
+
+                match self {
+                    $( $n0 => "", )*
+                    $( $n1(..) => $s1, )*
+                    $( $n2(..) => $s2, )*
+                    $( $n3(..) => $s3, )*
+                }
+            }
+
+            pub fn cycles(&self) -> u64 { //one to fetch, plus one for each access to a disc, or more for slow arithmetic
+                use self::Instruction::*;
+
+                match self {
+                    $( $n0 => $y0, )*
+                    $( $n1(..) => $y1, )*
+                    $( $n2(..) => $y2, )*
+                    $( $n3(..) => $y3, )*
+                }
+            }
+
+            pub fn operands(&self) -> Vec<u8> { //operand values, in assembly order
+                use self::Instruction::*;
+
+                match *self {
+                    $( $n0 => vec![], )*
+                    $( $n1(a) => vec![a], )*
+                    $( $n2(a, b) => vec![a, b], )*
+                    $( $n3(a, b, c) => vec![c, a, b], )*
+                }
+            }
+        }
+    };
+}
+
+isa! {
+    none {
+        Ret = 0b11010110, "RET", 3;
+        Rett = 0b11110000, "RETT", 1;
+    }
+    single {
+        End = 0b00000000, "END", "V", 1; //the operand is the program's exit status
+        Push = 0b11010111, "PUSH", "R", 2;
+        Pop = 0b11011000, "POP", "R", 2;
+        Popc = 0b11100111, "POPC", "R", 1;
+    }
+    pair {
+        SubRV = 0b01101000, "SUB", "RV", 1;
+        SubVR = 0b01101001, "SUB", "VR", 1;
+        SubRR = 0b01101010, "SUB", "RR", 1;
+        AddRV = 0b01101011, "ADD", "RV", 1;
+        AddRR = 0b01101100, "ADD", "RR", 1;
+        OrRV = 0b01101101, "OR", "RV", 1;
+        OrRR = 0b01101110, "OR", "RR", 1;
+        XorRV = 0b01101111, "XOR", "RV", 1;
+        XorRR = 0b01110000, "XOR", "RR", 1;
+        NorRV = 0b01110001, "NOR", "RV", 1;
+        NorRR = 0b01110010, "NOR", "RR", 1;
+        AndRV = 0b01110011, "AND", "RV", 1;
+        AndRR = 0b01110100, "AND", "RR", 1;
+        GrtRV = 0b01110101, "GRT", "RV", 1;
+        GrtRR = 0b01110110, "GRT", "RR", 1;
+        LstRV = 0b01110111, "LST", "RV", 1;
+        LstRR = 0b01111000, "LST", "RR", 1;
+        GreqtRV = 0b01111001, "GREQT", "RV", 1;
+        GreqtRR = 0b01111010, "GREQT", "RR", 1;
+        LseqtRV = 0b01111011, "LSEQT", "RV", 1;
+        LseqtRR = 0b01111100, "LSEQT", "RR", 1;
+        EqlRV = 0b01111101, "EQL", "RV", 1;
+        EqlRR = 0b01111110, "EQL", "RR", 1;
+        NeqlRV = 0b01111111, "NEQL", "RV", 1;
+        NeqlRR = 0b10000000, "NEQL", "RR", 1;
+        SetRV = 0b10000001, "SET", "RV", 1;
+        SetRR = 0b10000010, "SET", "RR", 1;
+        JmpDI = 0b10000011, "JMP", "VD", 1;
+        JmpDR = 0b10000100, "JMP", "RD", 1;
+        JmpRI = 0b10000101, "JMP", "VS", 1;
+        JmpRR = 0b10000110, "JMP", "RS", 1;
+        AdcRV = 0b11001000, "ADC", "RV", 1;
+        AdcRR = 0b11001001, "ADC", "RR", 1;
+        SbcRV = 0b11001010, "SBC", "RV", 1;
+        SbcRR = 0b11001011, "SBC", "RR", 1;
+        JcDI = 0b11001100, "JC", "VD", 1;
+        JncDI = 0b11001101, "JNC", "VD", 1;
+        JvDI = 0b11001110, "JV", "VD", 1;
+        JnvDI = 0b11001111, "JNV", "VD", 1;
+        JnDI = 0b11010000, "JN", "VD", 1;
+        JnnDI = 0b11010001, "JNN", "VD", 1;
+        JeDI = 0b11010010, "JE", "VD", 1;
+        JneDI = 0b11010011, "JNE", "VD", 1;
+        CallDI = 0b11010100, "CALL", "VD", 3;
+        CallDR = 0b11010101, "CALL", "RD", 3;
+        ShlRV = 0b11011001, "SHL", "RV", 1;
+        ShlRR = 0b11011010, "SHL", "RR", 1;
+        ShrRV = 0b11011011, "SHR", "RV", 1;
+        ShrRR = 0b11011100, "SHR", "RR", 1;
+        RolRV = 0b11011101, "ROL", "RV", 1;
+        RolRR = 0b11011110, "ROL", "RR", 1;
+        RorRV = 0b11011111, "ROR", "RV", 1;
+        RorRR = 0b11100000, "ROR", "RR", 1;
+        BtstRV = 0b11100001, "BTST", "RV", 1;
+        BtstRR = 0b11100010, "BTST", "RR", 1;
+        BsetRV = 0b11100011, "BSET", "RV", 1;
+        BsetRR = 0b11100100, "BSET", "RR", 1;
+        BclrRV = 0b11100101, "BCLR", "RV", 1;
+        BclrRR = 0b11100110, "BCLR", "RR", 1;
+        MulRV = 0b11101000, "MUL", "RV", 4;
+        MulRR = 0b11101001, "MUL", "RR", 4;
+        DivRV = 0b11101010, "DIV", "RV", 8;
+        DivVR = 0b11101011, "DIV", "VR", 8;
+        DivRR = 0b11101100, "DIV", "RR", 8;
+        ModRV = 0b11101101, "MOD", "RV", 8;
+        ModVR = 0b11101110, "MOD", "VR", 8;
+        ModRR = 0b11101111, "MOD", "RR", 8;
+        TrapDI = 0b11110001, "TRAP", "VD", 1;
+        TlocRR = 0b11110010, "TLOC", "RR", 1;
+    }
+    triple {
+        SaveDIV = 0b00001000, "SAVE", "DVV", 2;
+        SaveDIR = 0b00010000, "SAVE", "DVR", 2;
+        SaveDRV = 0b00011000, "SAVE", "DRV", 2;
+        SaveDRR = 0b00100000, "SAVE", "DRR", 2;
+        SaveRIV = 0b00101000, "SAVE", "SVV", 2;
+        SaveRIR = 0b00110000, "SAVE", "SVR", 2;
+        SaveRRV = 0b00111000, "SAVE", "SRV", 2;
+        SaveRRR = 0b01000000, "SAVE", "SRR", 2;
+        LoadDIR = 0b01001000, "LOAD", "DVR", 2;
+        LoadDRR = 0b01010000, "LOAD", "DRR", 2;
+        LoadRIR = 0b01011000, "LOAD", "SVR", 2;
+        LoadRRR = 0b01100000, "LOAD", "SRR", 2;
+        JzDRI = 0b10001000, "JZ", "DRV", 1;
+        JzDRR = 0b10010000, "JZ", "DRR", 1;
+        JzRRI = 0b10011000, "JZ", "SRV", 1;
+        JzRRR = 0b10100000, "JZ", "SRR", 1;
+        JnzDRI = 0b10101000, "JNZ", "DRV", 1;
+        JnzDRR = 0b10110000, "JNZ", "DRR", 1;
+        JnzRRI = 0b10111000, "JNZ", "SRV", 1;
+        JnzRRR = 0b11000000, "JNZ", "SRR", 1;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    UnknownOpcode(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnknownOpcode(op) => write!(f, "Opcode {:08b} is not a valid instruction.", op),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl fmt::Display for Instruction { //disassemble into rasm syntax
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == Instruction::End(0) { //the status is optional in rasm
+            return write!(f, "END");
+        }
+        write!(f, "{}", self.mnemonic())?;
+        for (kind, v) in self.syntax().chars().zip(self.operands()) {
+            match kind {
+                'R' => write!(f, " @{}", v)?,
+                'D' => write!(f, " :{}", v)?,
+                'S' => write!(f, " :@{}", v)?,
+                _ => write!(f, " {}", v)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+use crate::{Machine, RegisterWrite, MemoryWrite, TrapFrame};
+
+//the undo journal: enough about every recent instruction to take it back.
+//only the machine itself is rewound; anything a device did (printing, reading input) stays done
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    steps: u64, //counters and position from before the instruction ran
+    cycles: u64,
+    prgcount: u8,
+    dsccount: u8,
+    isactive: bool,
+    past_end: bool,
+    status: u8,
+    jmp: bool,
+    flags: u8,
+    sp: u8,
+    trap_vector: Option<(u8, u8)>,
+    trap_frame: Option<TrapFrame>,
+    registers: Vec<RegisterWrite>,
+    memory: Vec<MemoryWrite>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Journal {
+    entries: VecDeque<Entry>,
+    limit: usize, //most instructions remembered; 0 turns the journal off
+}
+
+impl Machine {
+    pub fn set_journal_limit(&mut self, limit: usize) { //how many instructions can be undone
+        self.journal.limit = limit;
+        while self.journal.entries.len() > limit {
+            self.journal.entries.pop_front();
+        }
+    }
+
+    pub fn journal_limit(&self) -> usize {
+        self.journal.limit
+    }
+
+    pub fn clear_journal(&mut self) {
+        self.journal.entries.clear();
+    }
+
+    pub fn journal_len(&self) -> usize { //how many instructions can be undone right now
+        self.journal.entries.len()
+    }
+
+    pub fn oldest_step(&self) -> u64 { //the furthest back rewind_to can go
+        match self.journal.entries.front() {
+            Some(x) => x.steps,
+            None => self.steps,
+        }
+    }
+
+    pub fn oldest_cycle(&self) -> u64 { //the furthest back rewind_to_cycle can go
+        match self.journal.entries.front() {
+            Some(x) => x.cycles,
+            None => self.cycles,
+        }
+    }
+
+    pub(crate) fn journaling(&self) -> bool {
+        self.journal.limit > 0
+    }
+
+    pub(crate) fn begin_entry(&self) -> Entry {
+        Entry {
+            steps: self.steps,
+            cycles: self.cycles,
+            prgcount: self.prgcount,
+            dsccount: self.dsccount,
+            isactive: self.isactive,
+            past_end: self.past_end,
+            status: self.status,
+            jmp: self.jmp,
+            flags: self.flags,
+            sp: self.sp,
+            trap_vector: self.trap_vector,
+            trap_frame: self.trap_frame,
+            registers: vec![],
+            memory: vec![],
+        }
+    }
+
+    pub(crate) fn commit_entry(&mut self, mut entry: Entry) { //file the writes the instruction made
+        entry.registers = self.reg_writes.clone();
+        entry.memory = self.mem_writes.clone();
+        self.journal.entries.push_back(entry);
+        if self.journal.entries.len() > self.journal.limit {
+            self.journal.entries.pop_front();
+        }
+    }
+
+    pub fn step_back(&mut self) -> bool { //undo the last instruction; false if the journal is empty
+        let entry = match self.journal.entries.pop_back() {
+            Some(x) => x,
+            None => return false,
+        };
+        for w in entry.memory.iter().rev() {
+            self.mem[w.disc as usize][w.index as usize] = w.old;
+        }
+        for w in entry.registers.iter().rev() {
+            self.registers[w.register as usize] = w.old;
+        }
+        self.steps = entry.steps;
+        self.cycles = entry.cycles;
+        self.prgcount = entry.prgcount;
+        self.dsccount = entry.dsccount;
+        self.isactive = entry.isactive;
+        self.past_end = entry.past_end;
+        self.status = entry.status;
+        self.jmp = entry.jmp;
+        self.flags = entry.flags;
+        self.sp = entry.sp;
+        self.trap_vector = entry.trap_vector;
+        self.trap_frame = entry.trap_frame;
+        self.resume = false;
+        self.hit = None;
+        true
+    }
+
+    pub fn rewind_to(&mut self, steps: u64) -> bool { //undo until `steps` instructions have run; false if the journal doesn't go back that far
+        if steps < self.oldest_step() || steps > self.steps {
+            return false;
+        }
+        while self.steps > steps && self.step_back() {}
+        true
+    }
+
+    pub fn rewind_to_cycle(&mut self, cycles: u64) -> bool { //undo until no more than `cycles` cycles have run, stopping between instructions; false if the journal doesn't go back that far
+        if cycles < self.oldest_cycle() || cycles > self.cycles {
+            return false;
+        }
+        while self.cycles > cycles && self.step_back() {}
+        true
+    }
+
+    pub fn back_to_register_write(&mut self, r: u8) -> bool { //undo up to and including the last instruction that wrote @r
+        self.back_to(|e| e.registers.iter().any(|w| w.register == r))
+    }
+
+    pub fn back_to_memory_write(&mut self, d: u8, i: u8) -> bool { //undo up to and including the last instruction that wrote :d i
+        self.back_to(|e| e.memory.iter().any(|w| w.disc == d && w.index == i))
+    }
+
+    fn back_to<F: Fn(&Entry) -> bool>(&mut self, wrote: F) -> bool {
+        let target = match self.journal.entries.iter().rev().find(|e| wrote(e)) {
+            Some(x) => x.steps,
+            None => return false,
+        };
+        self.rewind_to(target)
+    }
+}
@@ -2,103 +2,738 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 #![allow(unused_parens)]
-use std::num::Wrapping;
+use std::fmt;
+use std::collections::HashMap;
+
+mod error;
+pub mod isa;
+pub mod device;
+pub mod debug;
+pub mod trace;
+pub mod snapshot;
+pub mod config;
+mod journal;
+mod persist;
+pub use error::{Fault, VmError, LoadError, RunError};
+pub use device::{Device, Console, Capture, Input, ReadMode};
+pub use isa::{decode, encode, Instruction, DecodeError};
+pub use debug::{Access, MemoryWatch, Hit, SelfModify, CodeWrite};
+pub use trace::{Tracer, TraceEvent, RegisterWrite, MemoryWrite};
+pub use snapshot::SnapshotError;
+pub use config::{MachineConfig, StandardDevice, ConfigError};
+pub use persist::FLUSH_INDEX;
 
 fn u8ify(b: bool) -> u8 {
     if b {1} else {0}
 }
 
-pub const DISCS: usize = 8;
-pub const REGISTERS: usize = 8; //because they have to be accessable in the same number of bytes
+fn zn(x: u8) -> u8 { //zero and negative flags for a result
+    (if x == 0 {FLAG_ZERO} else {0}) | (if x&0x80 != 0 {FLAG_NEGATIVE} else {0})
+}
 
-struct Machine {
-    mem: [[u8; 256]; DISCS], //four port memory: port 1 is RAM and input/output, port 2 is the removable disc, the rest is the hard drive
-    registers: [u8; REGISTERS],
+pub const DISCS: usize = 8; //the standard geometry; see MachineConfig for others
+pub const REGISTERS: usize = 8; //because they have to be accessable in the same number of bytes
+pub const CONSOLE_DISC: u8 = 4;
+pub const INPUT_DISC: u8 = 5; //where input goes when asked for; see StandardDevice::Input
+pub const DISC_INSTRUCTIONS: usize = 256/3; //whole instructions that fit on a disc; fetch policies only decide what happens after the last
+pub const STACK_DISC: u8 = 6; //where CALL, RET, PUSH and POP keep the stack, unless told otherwise
+
+pub const FLAG_ZERO: u8 = 0b0001; //bits of the flags register, set by the ALU and comparisons
+pub const FLAG_CARRY: u8 = 0b0010; //carry out of an add, or borrow out of a subtract
+pub const FLAG_OVERFLOW: u8 = 0b0100; //signed overflow
+pub const FLAG_NEGATIVE: u8 = 0b1000; //top bit of the result
+
+pub struct Machine {
+    config: MachineConfig,
+    mem: Vec<[u8; 256]>, //four port memory: port 1 is RAM and input/output, port 2 is the removable disc, the rest is the hard drive
+    permissions: Vec<Permissions>,
+    registers: Vec<u8>,
+    flags: u8,
+    sp: u8, //next free byte of the stack, which grows upwards from index 0
+    stack_disc: u8,
+    trap_vector: Option<(u8, u8)>, //(disc, pc) of the fault handler
+    trap_frame: Option<TrapFrame>, //the fault being handled, while its handler runs
     prgcount: u8, //index on disc
     dsccount: u8, //current disc
     isactive: bool, //is the processor running?
+    past_end: bool, //has the pc run off the end of its disc? the next fetch faults
+    status: u8, //exit status given by END
     jmp: bool, //has a goto just been called?
+    devices: Vec<(u8, Box<dyn Device>)>, //devices and the discs they are mapped onto
+    steps: u64, //instructions executed
+    cycles: u64, //cycles spent executing them
+    budget: Budget,
+    fetch_policy: FetchPolicy,
+    breakpoints: Vec<(u8, u8)>, //(disc, pc) pairs to stop before
+    mem_watches: Vec<MemoryWatch>,
+    reg_watches: Vec<u8>,
+    hit: Option<Hit>, //first watchpoint tripped by the current instruction
+    strict: bool, //fault on undefined opcodes, rather than ending the program
+    undefined: Option<u8>, //undefined opcode the current instruction ended the program with
+    self_modify: SelfModify,
+    fetched: HashMap<(u8, u8), (u8, u8)>, //(disc, index) of every byte run as an instruction -> (disc, pc) of that instruction, unless self_modify is Allow
+    code_writes: Vec<CodeWrite>,
+    resume: bool, //step over the breakpoint we are stopped at
+    tracer: Option<Box<dyn Tracer>>,
+    reg_writes: Vec<RegisterWrite>, //what the current instruction has changed, while tracing or journaling
+    mem_writes: Vec<MemoryWrite>,
+    journal: journal::Journal,
+    backing: Vec<(u8, std::path::PathBuf)>, //discs saved to host files
+    flush_every: Option<u64>, //instructions between saving them
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget { //limits on how far `Machine::run` may go; None is unlimited
+    pub steps: Option<u64>,
+    pub cycles: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions { //what instructions may do with a disc; devices and the host are never stopped
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Permissions {
+    pub const ALL: Permissions = Permissions { read: true, write: true, execute: true };
+    pub const CODE: Permissions = Permissions { read: true, write: false, execute: true };
+    pub const DATA: Permissions = Permissions { read: true, write: true, execute: false };
+
+    pub fn allows(&self, access: Access) -> bool {
+        match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => self.execute,
+        }
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Permissions {
+        Permissions::ALL
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrapFrame { //what a fault handler interrupted
+    pub disc: u8, //where the fault happened: the instruction that faulted, or the last one run before the pc ran off the end of its disc
+    pub pc: u8,
+    pub saved: u8, //@0 from before the cause code replaced it; RETT puts it back
+    pub resume: Option<(u8, u8)>, //(disc, pc) RETT carries on at, if there is anywhere to
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FetchPolicy { //what happens to an instruction that runs off the end of its disc
+    NextDisc, //carry on at the start of the next disc
+    Wrap, //carry on at the start of the same disc
+    #[default]
+    Fault, //stop with FetchPastEnd
+}
+
+impl FetchPolicy {
+    pub fn locate(self, discs: usize, disc: u8, pc: u8, offset: u8) -> Result<(u8, u8), Fault> { //where the byte `offset` on from pc lives, on a machine with that many discs
+        let i = pc as u16 + offset as u16;
+        if i < 256 {
+            return Ok((disc, i as u8));
+        }
+        match self {
+            FetchPolicy::NextDisc if (disc as usize) + 1 < discs => Ok((disc + 1, i as u8)),
+            FetchPolicy::Wrap => Ok((disc, i as u8)),
+            _ => Err(Fault::FetchPastEnd),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop { //why `Machine::run` returned
+    Halted(u8), //the program ended, with this exit status
+    BudgetExhausted, //the step or cycle limit was reached first
+    Breakpoint { disc: u8, pc: u8 }, //about to execute the instruction at a breakpoint
+    Watchpoint(Hit), //the last instruction touched something being watched
+    UndefinedOpcode { opcode: u8, disc: u8, pc: u8 }, //not strict, so an opcode the instruction set doesn't define ended the program
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Halted(0) => write!(f, "program ended"),
+            Stop::Halted(status) => write!(f, "program ended with status {}", status),
+            Stop::BudgetExhausted => write!(f, "budget exhausted"),
+            Stop::Breakpoint { disc, pc } => write!(f, "breakpoint at disc {}, pc {}", disc, pc),
+            Stop::Watchpoint(hit) => write!(f, "watchpoint: {}", hit),
+            Stop::UndefinedOpcode { opcode, disc, pc } => write!(f, "undefined opcode {:08b} at disc {}, pc {}", opcode, disc, pc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome { //how a run finished, and the state it finished in
+    pub stop: Stop,
+    pub steps: u64,
+    pub cycles: u64,
+    pub disc: u8,
+    pub pc: u8,
+    pub registers: Vec<u8>,
 }
 
-pub fn run(program: Vec<[u8; 256]>) ->Result<(), &'static str> {
-    let mut vm = Machine { mem: [[0; 256]; DISCS], registers: [0; REGISTERS], prgcount: 0, dsccount: 0, isactive: true, jmp: false, }; //initialise vm
-    for (i, v) in program.iter().enumerate() {
-        vm.loaddsc(i as u8, *v)?;
-        if {i > 2} { //write max of 3 discs
-            break
-        }
-    }
-    while {vm.isactive} {
-        let ins: [u8; 3] = [vm.mem(vm.dsccount, vm.prgcount)?, vm.mem(vm.dsccount, vm.prgcount+1)?, vm.mem(vm.dsccount, vm.prgcount+2)?];
-        //println!("Initiating instruction: {:?} {:?} {:?} at position: {:?} {:?}", ins[0], ins[1], ins[2], vm.dsccount, vm.prgcount);
-        match ins[0] {
-            0b00000000 => vm.end_END()?,
-            //stuff here is special chars for assembly
-            0b00001000 ... 0b00001111 => vm.ram_SAV_DIV(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00010000 ... 0b00010111 => vm.ram_SAV_DIR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00011000 ... 0b00011111 => vm.ram_SAV_DRV(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00100000 ... 0b00100111 => vm.ram_SAV_DRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00101000 ... 0b00101111 => vm.ram_SAV_RIV(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00110000 ... 0b00110111 => vm.ram_SAV_RIR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b00111000 ... 0b00111111 => vm.ram_SAV_RRV(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01000000 ... 0b01000111 => vm.ram_SAV_RRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01001000 ... 0b01001111 => vm.ram_LOAD_DIR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01010000 ... 0b01010111 => vm.ram_LOAD_DRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01011000 ... 0b01011111 => vm.ram_LOAD_RIR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01100000 ... 0b01100111 => vm.ram_LOAD_DIR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b01101000 => vm.alu_SUB_RV(ins[1], ins[2])?,
-            0b01101001 => vm.alu_SUB_VR(ins[1], ins[2])?,
-            0b01101010 => vm.alu_SUB_RR(ins[1], ins[2])?,
-            0b01101011 => vm.alu_ADD_RV(ins[1], ins[2])?,
-            0b01101100 => vm.alu_ADD_RR(ins[1], ins[2])?,
-            0b01101101 => vm.alu_OR_RV(ins[1], ins[2])?,
-            0b01101110 => vm.alu_OR_RR(ins[1], ins[2])?,
-            0b01101111 => vm.alu_XOR_RV(ins[1], ins[2])?,
-            0b01110000 => vm.alu_XOR_RR(ins[1], ins[2])?,
-            0b01110001 => vm.alu_NOR_RV(ins[1], ins[2])?,
-            0b01110010 => vm.alu_NOR_RR(ins[1], ins[2])?,
-            0b01110011 => vm.alu_AND_RV(ins[1], ins[2])?,
-            0b01110100 => vm.alu_AND_RR(ins[1], ins[2])?,
-            0b01110101 => vm.cmp_GRT_RV(ins[1], ins[2])?,
-            0b01110110 => vm.cmp_GRT_RR(ins[1], ins[2])?,
-            0b01110111 => vm.cmp_LST_RV(ins[1], ins[2])?,
-            0b01111000 => vm.cmp_LST_RR(ins[1], ins[2])?,
-            0b01111001 => vm.cmp_GREQT_RV(ins[1], ins[2])?,
-            0b01111010 => vm.cmp_GREQT_RR(ins[1], ins[2])?,
-            0b01111011 => vm.cmp_LSEQT_RV(ins[1], ins[2])?,
-            0b01111100 => vm.cmp_LSEQT_RR(ins[1], ins[2])?,
-            0b01111101 => vm.cmp_EQL_RV(ins[1], ins[2])?,
-            0b01111110 => vm.cmp_EQL_RR(ins[1], ins[2])?,
-            0b01111111 => vm.cmp_NEQL_RV(ins[1], ins[2])?,
-            0b10000000 => vm.cmp_NEQL_RR(ins[1], ins[2])?,
-            0b10000001 => vm.reg_SET_RV(ins[1], ins[2])?,
-            0b10000010 => vm.reg_SET_RR(ins[1], ins[2])?,
-            0b10000011 => vm.goto_UNCON_DI(ins[1], ins[2])?,
-            0b10000100 => vm.goto_UNCON_DR(ins[1], ins[2])?,
-            0b10000101 => vm.goto_UNCON_RI(ins[1], ins[2])?,
-            0b10000110 => vm.goto_UNCON_RR(ins[1], ins[2])?,
-            //stuff here is special chars for assembly
-            0b10001000 ... 0b10001111 => vm.goto_ZRO_DRI(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10010000 ... 0b10010111 => vm.goto_ZRO_DRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10011000 ... 0b10011111 => vm.goto_ZRO_RRI(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10100000 ... 0b10100111 => vm.goto_ZRO_RRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10101000 ... 0b10101111 => vm.goto_NZRO_DRI(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10110000 ... 0b10110111 => vm.goto_NZRO_DRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b10111000 ... 0b10111111 => vm.goto_NZRO_RRI(ins[1], ins[2], ins[0]&0b00000111)?,
-            0b11000000 ... 0b11000111 => vm.goto_NZRO_RRR(ins[1], ins[2], ins[0]&0b00000111)?,
-            //stuff here is special chars for assembly
-            _ => vm.end_END()?
-        }
-        if {vm.mem(4, 1)? != 0} {
-            print!("{}", vm.mem(4, 0)? as char);
-            vm.sav(4, 1, 0)?;
-        }
-        vm.next_ins()?;
-    }
-    Ok(())
+pub fn run(program: Vec<[u8; 256]>) -> Result<Outcome, RunError> {
+    run_limited(program, Budget::default())
+}
+
+pub fn run_limited(program: Vec<[u8; 256]>, budget: Budget) -> Result<Outcome, RunError> {
+    let mut vm = boot(&program)?;
+    vm.set_budget(budget);
+    let stop = vm.run()?;
+    Ok(vm.outcome(stop))
+}
+
+pub fn boot(program: &[[u8; 256]]) -> Result<Machine, LoadError> { //a machine with the program loaded and the standard devices attached
+    boot_with(Machine::new(), program)
+}
+
+pub fn boot_with(mut vm: Machine, program: &[[u8; 256]]) -> Result<Machine, LoadError> { //like `boot`, for a machine built from any config
+    vm.load_program(program)?;
+    for (d, device) in vm.config.devices.clone() {
+        let device: Box<dyn Device> = match device {
+            StandardDevice::Console => Box::new(Console::stdout()),
+            StandardDevice::Input(mode) => Box::new(Input::stdin(mode)),
+        };
+        vm.attach(d, device).map_err(|_| LoadError::NoDisc(d))?;
+    }
+    Ok(vm)
+}
+
+impl Default for Machine {
+    fn default() -> Machine {
+        Machine::new()
+    }
 }
 
 impl Machine {
+    pub fn new() -> Machine {
+        Machine::with_config(MachineConfig::default()).unwrap()
+    }
+
+    pub fn with_config(config: MachineConfig) -> Result<Machine, ConfigError> {
+        config.validate()?;
+        Ok(Machine {
+            mem: vec![[0; 256]; config.discs],
+            permissions: vec![Permissions::ALL; config.discs],
+            registers: vec![0; config.registers],
+            flags: 0,
+            sp: 0,
+            stack_disc: config.stack_disc,
+            trap_vector: None,
+            trap_frame: None,
+            prgcount: 0,
+            dsccount: 0,
+            isactive: true,
+            past_end: false,
+            status: 0,
+            jmp: false,
+            devices: vec![],
+            steps: 0,
+            cycles: 0,
+            budget: Budget::default(),
+            fetch_policy: FetchPolicy::default(),
+            breakpoints: vec![],
+            mem_watches: vec![],
+            reg_watches: vec![],
+            hit: None,
+            strict: false, //undefined opcodes have always ended the program, and old programs rely on it
+            undefined: None,
+            self_modify: SelfModify::default(),
+            fetched: HashMap::new(),
+            code_writes: vec![],
+            resume: false,
+            tracer: None,
+            reg_writes: vec![],
+            mem_writes: vec![],
+            journal: journal::Journal::default(),
+            backing: vec![],
+            flush_every: None,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &MachineConfig {
+        &self.config
+    }
+
+    pub fn load_program(&mut self, program: &[[u8; 256]]) -> Result<(), LoadError> { //onto the config's program discs, in order
+        self.load_mapped(program, &self.config.program_discs.clone())
+    }
+
+    pub fn load_mapped(&mut self, program: &[[u8; 256]], map: &[u8]) -> Result<(), LoadError> { //disc i of the program goes onto disc map[i]
+        if program.len() > map.len() {
+            return Err(LoadError::DoesNotFit { sections: program.len(), discs: map.len() });
+        }
+        if let Some(&d) = map.iter().take(program.len()).find(|&&d| self.getdsc(d).is_err()) {
+            return Err(LoadError::NoDisc(d));
+        }
+        for (&d, v) in map.iter().zip(program) {
+            self.mem[d as usize] = *v;
+            self.forget_fetched(d);
+        }
+        Ok(())
+    }
+
+    pub fn load_disc(&mut self, d: u8, f: [u8; 256]) -> Result<(), LoadError> {
+        let d = self.getdsc(d).map_err(|_| LoadError::NoDisc(d))?;
+        self.mem[d as usize] = f;
+        self.forget_fetched(d);
+        Ok(())
+    }
+
+    pub fn permissions(&self, d: u8) -> Option<Permissions> {
+        self.permissions.get(d as usize).copied()
+    }
+
+    pub fn set_permissions(&mut self, d: u8, permissions: Permissions) -> Result<(), Fault> {
+        let d = self.getdsc(d)? as usize;
+        self.permissions[d] = permissions;
+        Ok(())
+    }
+
+    pub fn attach(&mut self, d: u8, device: Box<dyn Device>) -> Result<(), Fault> { //map a device onto a disc, replacing any already there
+        let d = self.getdsc(d)?;
+        self.detach(d);
+        self.devices.push((d, device));
+        Ok(())
+    }
+
+    pub fn detach(&mut self, d: u8) -> Option<Box<dyn Device>> {
+        let i = self.devices.iter().position(|(n, _)| *n == d)?;
+        Some(self.devices.remove(i).1)
+    }
+
+    pub fn registers(&self) -> &[u8] {
+        &self.registers
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn sp(&self) -> u8 {
+        self.sp
+    }
+
+    pub fn stack_disc(&self) -> u8 {
+        self.stack_disc
+    }
+
+    pub fn set_stack_disc(&mut self, d: u8) -> Result<(), ConfigError> { //move the stack, emptying it; the config follows it
+        let config = MachineConfig { stack_disc: d, ..self.config.clone() };
+        config.validate()?;
+        self.config = config;
+        self.stack_disc = d;
+        self.sp = 0;
+        Ok(())
+    }
+
+    pub fn trap_vector(&self) -> Option<(u8, u8)> {
+        self.trap_vector
+    }
+
+    pub fn set_trap_vector(&mut self, vector: Option<(u8, u8)>) -> Result<(), Fault> { //where faults go instead of stopping the machine
+        if let Some((d, _)) = vector {
+            self.getdsc(d)?;
+        }
+        self.trap_vector = vector;
+        Ok(())
+    }
+
+    pub fn trap_frame(&self) -> Option<TrapFrame> { //the fault being handled
+        self.trap_frame
+    }
+
+    pub fn prgcount(&self) -> u8 {
+        self.prgcount
+    }
+
+    pub fn dsccount(&self) -> u8 {
+        self.dsccount
+    }
+
+    pub fn disc(&self, d: u8) -> Option<&[u8; 256]> {
+        self.mem.get(d as usize)
+    }
+
+    pub fn set_register(&mut self, r: u8, v: u8) -> Result<(), Fault> { //poke a register from outside, without tripping watchpoints
+        match self.registers.get_mut(r as usize) {
+            Some(x) => {*x = v; Ok(())},
+            None => Err(Fault::BadRegister(r)),
+        }
+    }
+
+    pub fn poke(&mut self, d: u8, i: u8, v: u8) -> Result<(), Fault> { //write memory from outside, bypassing devices and watchpoints
+        let d = self.getdsc(d)?;
+        self.mem[d as usize][i as usize] = v;
+        self.fetched.remove(&(d, i)); //not the byte that was run any more
+        Ok(())
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.isactive
+    }
+
+    pub fn is_past_end(&self) -> bool { //ran off the end of a disc, so the next fetch will fault
+        self.past_end
+    }
+
+    pub fn status(&self) -> u8 { //exit status of a program that has ended
+        self.status
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn budget(&self) -> Budget {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) { //limits count from power-on, not from when they were set
+        self.budget = budget;
+    }
+
+    pub fn fetch_policy(&self) -> FetchPolicy {
+        self.fetch_policy
+    }
+
+    pub fn set_fetch_policy(&mut self, policy: FetchPolicy) {
+        self.fetch_policy = policy;
+    }
+
+    pub fn locate(&self, d: u8, pc: u8, offset: u8) -> Result<(u8, u8), Fault> { //where the byte `offset` on from pc is fetched from, under the fetch policy
+        self.fetch_policy.locate(self.mem.len(), d, pc, offset)
+    }
+
+    pub fn instruction_at(&self, d: u8, pc: u8) -> Result<[u8; 3], Fault> { //the bytes the instruction at pc is fetched from, under the fetch policy
+        let mut ins = [0; 3];
+        for (k, b) in ins.iter_mut().enumerate() {
+            let (d, i) = self.locate(d, pc, k as u8)?;
+            *b = self.peek(d, i)?;
+        }
+        Ok(ins)
+    }
+
+    pub fn outcome(&self, stop: Stop) -> Outcome {
+        Outcome { stop, steps: self.steps, cycles: self.cycles, disc: self.dsccount, pc: self.prgcount, registers: self.registers.clone() }
+    }
+
+    fn exhausted(&self) -> bool {
+        self.budget.steps.is_some_and(|n| self.steps >= n) || self.budget.cycles.is_some_and(|n| self.cycles >= n)
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self, strict: bool) { //make undefined opcodes an IllegalInstruction fault
+        self.strict = strict;
+    }
+
+    pub fn self_modify(&self) -> SelfModify {
+        self.self_modify
+    }
+
+    pub fn set_self_modify(&mut self, mode: SelfModify) { //only instructions fetched from now on are watched
+        self.self_modify = mode;
+        if mode == SelfModify::Allow {
+            self.fetched.clear();
+        }
+    }
+
+    pub(crate) fn forget_fetched(&mut self, d: u8) { //the host put a new image on the disc, none of which has run yet
+        self.fetched.retain(|&(e, _), _| e != d);
+    }
+
+    pub fn code_writes(&self) -> &[CodeWrite] { //what SelfModify::Report has seen so far, once per writer and target
+        &self.code_writes
+    }
+
+    pub fn add_breakpoint(&mut self, disc: u8, pc: u8) {
+        if !self.breakpoints.contains(&(disc, pc)) {
+            self.breakpoints.push((disc, pc));
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, disc: u8, pc: u8) -> bool {
+        let before = self.breakpoints.len();
+        self.breakpoints.retain(|b| *b != (disc, pc));
+        self.breakpoints.len() != before
+    }
+
+    pub fn breakpoints(&self) -> &[(u8, u8)] {
+        &self.breakpoints
+    }
+
+    pub fn watch_memory(&mut self, watch: MemoryWatch) {
+        self.mem_watches.push(watch);
+    }
+
+    pub fn unwatch_memory(&mut self, disc: u8, i: u8) -> bool { //remove every memory watch covering :disc i
+        let before = self.mem_watches.len();
+        self.mem_watches.retain(|w| !(w.disc == disc && w.from <= i && i <= w.to));
+        self.mem_watches.len() != before
+    }
+
+    pub fn memory_watches(&self) -> &[MemoryWatch] {
+        &self.mem_watches
+    }
+
+    pub fn watch_register(&mut self, r: u8) {
+        if !self.reg_watches.contains(&r) {
+            self.reg_watches.push(r);
+        }
+    }
+
+    pub fn unwatch_register(&mut self, r: u8) -> bool {
+        let before = self.reg_watches.len();
+        self.reg_watches.retain(|w| *w != r);
+        self.reg_watches.len() != before
+    }
+
+    pub fn register_watches(&self) -> &[u8] {
+        &self.reg_watches
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Box<dyn Tracer>>) {
+        self.tracer = tracer;
+    }
+
+    pub fn run(&mut self) -> Result<Stop, VmError> { //run until the program ends, the budget runs out or a breakpoint is hit
+        loop {
+            if let Some(stop) = self.advance()? {
+                return Ok(stop);
+            }
+        }
+    }
+
+    pub fn run_for(&mut self, n: usize) -> Result<Option<Stop>, VmError> { //like `run`, but give up after n instructions
+        for _ in 0..n {
+            if let Some(stop) = self.advance()? {
+                return Ok(Some(stop));
+            }
+        }
+        Ok(None)
+    }
+
+    fn advance(&mut self) -> Result<Option<Stop>, VmError> { //check for reasons to stop, then execute an instruction
+        if !self.isactive {
+            return Ok(Some(Stop::Halted(self.status)));
+        }
+        if self.exhausted() {
+            return Ok(Some(Stop::BudgetExhausted));
+        }
+        if !self.resume && self.breakpoints.contains(&(self.dsccount, self.prgcount)) {
+            self.resume = true;
+            return Ok(Some(Stop::Breakpoint { disc: self.dsccount, pc: self.prgcount }));
+        }
+        self.step()
+    }
+
+    pub fn step(&mut self) -> Result<Option<Stop>, VmError> { //execute a single instruction, ignoring breakpoints and the budget
+        if !self.isactive {
+            return Ok(Some(Stop::Halted(self.status)));
+        }
+        self.resume = false;
+        self.hit = None;
+        self.undefined = None;
+        self.reg_writes.clear();
+        self.mem_writes.clear();
+        let (disc, pc) = (self.dsccount, self.prgcount);
+        let entry = self.begin_entry();
+        let fetched = self.fetch();
+        if fetched.is_ok() && self.self_modify != SelfModify::Allow {
+            for k in 0..3 {
+                if let Ok(at) = self.locate(disc, pc, k) {
+                    self.fetched.insert(at, (disc, pc));
+                }
+            }
+        }
+        let ins = *fetched.as_ref().unwrap_or(&[0; 3]);
+        let ran = fetched.is_ok();
+        let result = fetched.and_then(|ins| self.execute(ins)).or_else(|f| self.trap(f));
+        let wrote = !self.reg_writes.is_empty() || !self.mem_writes.is_empty(); //a faulting instruction, or taking a trap, may have
+        if self.journaling() && (ran || wrote) { //nothing to undo if the instruction couldn't even be fetched
+            self.commit_entry(entry);
+        }
+        result.map_err(|f| self.fault(f, ins))?;
+        if let (true, Some(tracer)) = (ran, self.tracer.as_mut()) { //a trapped fetch ran nothing, so there is nothing to trace
+            let event = TraceEvent {
+                step: self.steps,
+                disc,
+                pc,
+                ins,
+                instruction: decode(ins).ok(),
+                registers: self.reg_writes.clone(),
+                memory: self.mem_writes.clone(),
+            };
+            if let Err(f) = tracer.trace(&event) {
+                return Err(VmError { fault: f, disc, pc, ins, registers: self.registers.clone() });
+            }
+        }
+        if let Some(hit) = self.hit.take() {
+            Ok(Some(Stop::Watchpoint(hit)))
+        } else if let Some(opcode) = self.undefined.take() {
+            Ok(Some(Stop::UndefinedOpcode { opcode, disc, pc }))
+        } else if !self.isactive {
+            Ok(Some(Stop::Halted(self.status)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn fetch(&self) -> Result<[u8; 3], Fault> {
+        if self.past_end {
+            return Err(Fault::FetchPastEnd);
+        }
+        for k in 0..3 {
+            let (d, i) = self.locate(self.dsccount, self.prgcount, k)?;
+            self.allow(d, i, Access::Execute)?;
+        }
+        self.instruction_at(self.dsccount, self.prgcount)
+    }
+
+    fn fault(&self, fault: Fault, ins: [u8; 3]) -> VmError { //attach the current location to a fault
+        VmError { fault, disc: self.dsccount, pc: self.prgcount, ins, registers: self.registers.clone() }
+    }
+
+    fn execute(&mut self, ins: [u8; 3]) -> Result<(), Fault> {
+        //println!("Initiating instruction: {:?} {:?} {:?} at position: {:?} {:?}", ins[0], ins[1], ins[2], self.dsccount, self.prgcount);
+        self.steps += 1;
+        match decode(ins) {
+            Ok(x) => {
+                self.cycles += x.cycles();
+                self.dispatch(x)?
+            },
+            Err(err) => {
+                self.cycles += 1;
+                if self.strict {
+                    return Err(err.into());
+                }
+                self.undefined = Some(ins[0]);
+                self.end_END(0)? //undefined opcodes halt the machine
+            },
+        }
+        for (d, device) in self.devices.iter_mut() {
+            device.tick(&mut self.mem[*d as usize])?;
+        }
+        self.tick_flush()?;
+        self.next_ins();
+        Ok(())
+    }
+
+    fn dispatch(&mut self, ins: Instruction) -> Result<(), Fault> {
+        use isa::Instruction::*;
+
+        match ins {
+            End(a) => self.end_END(a),
+            SaveDIV(a, b, c) => self.ram_SAV_DIV(a, b, c),
+            SaveDIR(a, b, c) => self.ram_SAV_DIR(a, b, c),
+            SaveDRV(a, b, c) => self.ram_SAV_DRV(a, b, c),
+            SaveDRR(a, b, c) => self.ram_SAV_DRR(a, b, c),
+            SaveRIV(a, b, c) => self.ram_SAV_RIV(a, b, c),
+            SaveRIR(a, b, c) => self.ram_SAV_RIR(a, b, c),
+            SaveRRV(a, b, c) => self.ram_SAV_RRV(a, b, c),
+            SaveRRR(a, b, c) => self.ram_SAV_RRR(a, b, c),
+            LoadDIR(a, b, c) => self.ram_LOAD_DIR(a, b, c),
+            LoadDRR(a, b, c) => self.ram_LOAD_DRR(a, b, c),
+            LoadRIR(a, b, c) => self.ram_LOAD_RIR(a, b, c),
+            LoadRRR(a, b, c) => self.ram_LOAD_RRR(a, b, c),
+            SubRV(a, b) => self.alu_SUB_RV(a, b),
+            SubVR(a, b) => self.alu_SUB_VR(a, b),
+            SubRR(a, b) => self.alu_SUB_RR(a, b),
+            AddRV(a, b) => self.alu_ADD_RV(a, b),
+            AddRR(a, b) => self.alu_ADD_RR(a, b),
+            OrRV(a, b) => self.alu_OR_RV(a, b),
+            OrRR(a, b) => self.alu_OR_RR(a, b),
+            XorRV(a, b) => self.alu_XOR_RV(a, b),
+            XorRR(a, b) => self.alu_XOR_RR(a, b),
+            NorRV(a, b) => self.alu_NOR_RV(a, b),
+            NorRR(a, b) => self.alu_NOR_RR(a, b),
+            AndRV(a, b) => self.alu_AND_RV(a, b),
+            AndRR(a, b) => self.alu_AND_RR(a, b),
+            GrtRV(a, b) => self.cmp_GRT_RV(a, b),
+            GrtRR(a, b) => self.cmp_GRT_RR(a, b),
+            LstRV(a, b) => self.cmp_LST_RV(a, b),
+            LstRR(a, b) => self.cmp_LST_RR(a, b),
+            GreqtRV(a, b) => self.cmp_GREQT_RV(a, b),
+            GreqtRR(a, b) => self.cmp_GREQT_RR(a, b),
+            LseqtRV(a, b) => self.cmp_LSEQT_RV(a, b),
+            LseqtRR(a, b) => self.cmp_LSEQT_RR(a, b),
+            EqlRV(a, b) => self.cmp_EQL_RV(a, b),
+            EqlRR(a, b) => self.cmp_EQL_RR(a, b),
+            NeqlRV(a, b) => self.cmp_NEQL_RV(a, b),
+            NeqlRR(a, b) => self.cmp_NEQL_RR(a, b),
+            SetRV(a, b) => self.reg_SET_RV(a, b),
+            SetRR(a, b) => self.reg_SET_RR(a, b),
+            JmpDI(a, b) => self.goto_UNCON_DI(a, b),
+            JmpDR(a, b) => self.goto_UNCON_DR(a, b),
+            JmpRI(a, b) => self.goto_UNCON_RI(a, b),
+            JmpRR(a, b) => self.goto_UNCON_RR(a, b),
+            JzDRI(a, b, c) => self.goto_ZRO_DRI(a, b, c),
+            JzDRR(a, b, c) => self.goto_ZRO_DRR(a, b, c),
+            JzRRI(a, b, c) => self.goto_ZRO_RRI(a, b, c),
+            JzRRR(a, b, c) => self.goto_ZRO_RRR(a, b, c),
+            JnzDRI(a, b, c) => self.goto_NZRO_DRI(a, b, c),
+            JnzDRR(a, b, c) => self.goto_NZRO_DRR(a, b, c),
+            JnzRRI(a, b, c) => self.goto_NZRO_RRI(a, b, c),
+            JnzRRR(a, b, c) => self.goto_NZRO_RRR(a, b, c),
+            AdcRV(a, b) => self.alu_ADC_RV(a, b),
+            AdcRR(a, b) => self.alu_ADC_RR(a, b),
+            SbcRV(a, b) => self.alu_SBC_RV(a, b),
+            SbcRR(a, b) => self.alu_SBC_RR(a, b),
+            JcDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_CARRY, true),
+            JncDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_CARRY, false),
+            JvDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_OVERFLOW, true),
+            JnvDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_OVERFLOW, false),
+            JnDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_NEGATIVE, true),
+            JnnDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_NEGATIVE, false),
+            JeDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_ZERO, true),
+            JneDI(a, b) => self.goto_FLAG_DI(a, b, FLAG_ZERO, false),
+            CallDI(a, b) => self.stk_CALL_DI(a, b),
+            CallDR(a, b) => self.stk_CALL_DR(a, b),
+            Ret => self.stk_RET(),
+            Push(a) => self.stk_PUSH_R(a),
+            Pop(a) => self.stk_POP_R(a),
+            ShlRV(a, b) => self.alu_SHL_RV(a, b),
+            ShlRR(a, b) => self.alu_SHL_RR(a, b),
+            ShrRV(a, b) => self.alu_SHR_RV(a, b),
+            ShrRR(a, b) => self.alu_SHR_RR(a, b),
+            RolRV(a, b) => self.alu_ROL_RV(a, b),
+            RolRR(a, b) => self.alu_ROL_RR(a, b),
+            RorRV(a, b) => self.alu_ROR_RV(a, b),
+            RorRR(a, b) => self.alu_ROR_RR(a, b),
+            BtstRV(a, b) => self.alu_BTST_RV(a, b),
+            BtstRR(a, b) => self.alu_BTST_RR(a, b),
+            BsetRV(a, b) => self.alu_BSET_RV(a, b),
+            BsetRR(a, b) => self.alu_BSET_RR(a, b),
+            BclrRV(a, b) => self.alu_BCLR_RV(a, b),
+            BclrRR(a, b) => self.alu_BCLR_RR(a, b),
+            Popc(a) => self.alu_POPC_R(a),
+            Rett => self.trap_RETT(),
+            TrapDI(a, b) => self.trap_TRAP_DI(a, b),
+            TlocRR(a, b) => self.trap_TLOC_RR(a, b),
+            MulRV(a, b) => self.alu_MUL_RV(a, b),
+            MulRR(a, b) => self.alu_MUL_RR(a, b),
+            DivRV(a, b) => self.alu_DIV_RV(a, b),
+            DivVR(a, b) => self.alu_DIV_VR(a, b),
+            DivRR(a, b) => self.alu_DIV_RR(a, b),
+            ModRV(a, b) => self.alu_MOD_RV(a, b),
+            ModVR(a, b) => self.alu_MOD_VR(a, b),
+            ModRR(a, b) => self.alu_MOD_RR(a, b),
+        }
+    }
+
     //instructions:
     //instruction naming scheme:
     //[type]_[INS]_[VAL USE]
@@ -108,287 +743,666 @@ impl Machine {
     //e.g. VR uses a program-specified value and a register;
     //letters can be V, R, I (index)
     //Current total of 12 embedded disc functions and 25 other functions
-    fn ram_SAV_DIV(&mut self, i: u8, v: u8, d: u8) -> Result<(), &'static str> {
+    fn ram_SAV_DIV(&mut self, i: u8, v: u8, d: u8) -> Result<(), Fault> {
         self.sav(d, i, v)
     }
 
-    fn ram_SAV_DIR(&mut self, i: u8, r: u8, d: u8) -> Result<(), &'static str> {
+    fn ram_SAV_DIR(&mut self, i: u8, r: u8, d: u8) -> Result<(), Fault> {
         self.sav(d, i, self.reg(r)?)
     }
 
-    fn ram_SAV_DRV(&mut self, r1: u8, v: u8, d: u8) -> Result<(), &'static str> {
+    fn ram_SAV_DRV(&mut self, r1: u8, v: u8, d: u8) -> Result<(), Fault> {
         self.sav(d, self.reg(r1)?, v)
     }
 
-    fn ram_SAV_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), &'static str> {
+    fn ram_SAV_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), Fault> {
         self.sav(d, self.reg(r1)?, self.reg(r2)?)
     }
 
-    fn ram_SAV_RIV(&mut self, i: u8, v: u8, r1: u8) -> Result<(), &'static str> {
+    fn ram_SAV_RIV(&mut self, i: u8, v: u8, r1: u8) -> Result<(), Fault> {
         self.sav(self.reg(r1)?, i, v)
     }
 
-    fn ram_SAV_RIR(&mut self, i: u8, r1: u8, r2: u8) -> Result<(), &'static str> {
+    fn ram_SAV_RIR(&mut self, i: u8, r1: u8, r2: u8) -> Result<(), Fault> {
         self.sav(self.reg(r2)?, i, self.reg(r1)?)
     }
 
-    fn ram_SAV_RRV(&mut self, r1: u8, v: u8, r2: u8) -> Result<(), &'static str> {
+    fn ram_SAV_RRV(&mut self, r1: u8, v: u8, r2: u8) -> Result<(), Fault> {
         self.sav(self.reg(r2)?, self.reg(r1)?, v)
     }
 
-    fn ram_SAV_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), &'static str> {
+    fn ram_SAV_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), Fault> {
         self.sav(self.reg(r3)?, self.reg(r1)?, self.reg(r2)?)
     }
 
-    fn ram_LOAD_DIR(&mut self, i: u8, r: u8, d: u8) -> Result<(), &'static str> {
-        self.set(r, self.mem(d, i)?)
+    fn ram_LOAD_DIR(&mut self, i: u8, r: u8, d: u8) -> Result<(), Fault> {
+        let v = self.mem(d, i)?;
+        self.set(r, v)
     }
 
-    fn ram_LOAD_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), &'static str> {
-        self.set(r2, self.mem(d, r2)?)
+    fn ram_LOAD_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), Fault> { //load index @r1 into @r2
+        let v = self.mem(d, self.reg(r1)?)?;
+        self.set(r2, v)
     }
 
-    fn ram_LOAD_RIR(&mut self, i: u8, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(r1, self.mem(self.reg(r2)?, i)?)
+    fn ram_LOAD_RIR(&mut self, i: u8, r1: u8, r2: u8) -> Result<(), Fault> {
+        let v = self.mem(self.reg(r2)?, i)?;
+        self.set(r1, v)
     }
 
-    fn ram_LOAD_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), &'static str> {
-        self.set(r2, self.mem(self.reg(r3)?, r2)?)
+    fn ram_LOAD_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), Fault> { //load index @r1 of disc @r3 into @r2
+        let v = self.mem(self.reg(r3)?, self.reg(r1)?)?;
+        self.set(r2, v)
     }
 
-    fn reg_SET_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
+    fn reg_SET_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
         self.set(r1, v)
     }
 
-    fn reg_SET_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
+    fn reg_SET_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
         self.set(r1, self.reg(r2)?)
     }
 
-    fn alu_SUB_RV(&mut self, r: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, (Wrapping(self.reg(r)?)-Wrapping(v)).0)
+    fn alu_SUB_RV(&mut self, r: u8, v: u8) -> Result<(), Fault> {
+        let x = self.sub(self.reg(r)?, v, 0);
+        self.set(0, x)
+    }
+
+    fn alu_SUB_VR(&mut self, v: u8, r: u8) -> Result<(), Fault> {
+        let x = self.sub(v, self.reg(r)?, 0);
+        self.set(0, x)
+    }
+
+    fn alu_SUB_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.sub(self.reg(r1)?, self.reg(r2)?, 0);
+        self.set(0, x)
+    }
+
+    fn alu_ADD_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let x = self.add(self.reg(r1)?, v, 0);
+        self.set(0, x)
+    }
+
+    fn alu_ADD_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.add(self.reg(r1)?, self.reg(r2)?, 0);
+        self.set(0, x)
+    }
+
+    fn alu_OR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?|v);
+        self.set(0, x)
+    }
+
+    fn alu_OR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?|self.reg(r2)?);
+        self.set(0, x)
+    }
+
+    fn alu_XOR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?^v);
+        self.set(0, x)
+    }
+
+    fn alu_XOR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?^self.reg(r2)?);
+        self.set(0, x)
+    }
+
+    fn alu_NOR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let x = self.logic(!(self.reg(r1)?|v));
+        self.set(0, x)
+    }
+
+    fn alu_NOR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.logic(!(self.reg(r1)?|self.reg(r2)?));
+        self.set(0, x)
+    }
+
+    fn alu_AND_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?&v);
+        self.set(0, x)
+    }
+
+    fn alu_AND_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?&self.reg(r2)?);
+        self.set(0, x)
+    }
+
+    fn alu_ADC_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //add with carry in
+        let x = self.add(self.reg(r1)?, v, self.carry());
+        self.set(0, x)
+    }
+
+    fn alu_ADC_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.add(self.reg(r1)?, self.reg(r2)?, self.carry());
+        self.set(0, x)
+    }
+
+    fn alu_SBC_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //subtract with borrow in
+        let x = self.sub(self.reg(r1)?, v, self.carry());
+        self.set(0, x)
+    }
+
+    fn alu_SBC_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.sub(self.reg(r1)?, self.reg(r2)?, self.carry());
+        self.set(0, x)
+    }
+
+    fn alu_SHL_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //shift left, carrying out the last bit lost
+        let x = self.shl(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_SUB_VR(&mut self, v: u8, r: u8) -> Result<(), &'static str> {
-        self.set(0, (Wrapping(v)-Wrapping(self.reg(r)?)).0)
+    fn alu_SHL_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.shl(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_SUB_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, (Wrapping(self.reg(r1)?)-Wrapping(self.reg(r2)?)).0)
+    fn alu_SHR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //shift right, carrying out the last bit lost
+        let x = self.shr(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_ADD_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, (Wrapping(self.reg(r1)?)+Wrapping(v)).0)
+    fn alu_SHR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.shr(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_ADD_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, (Wrapping(self.reg(r1)?)+Wrapping(self.reg(r2)?)).0)
+    fn alu_ROL_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //rotate left
+        let x = self.rol(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_OR_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?|v)
+    fn alu_ROL_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.rol(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_OR_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?|self.reg(r2)?)
+    fn alu_ROR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //rotate right
+        let x = self.ror(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_XOR_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?^v)
+    fn alu_ROR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.ror(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_XOR_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?^self.reg(r2)?)
+    fn alu_BSET_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //set a bit
+        let x = self.bset(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_NOR_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, !(self.reg(r1)?|v))
+    fn alu_BSET_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.bset(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_NOR_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, !(self.reg(r1)?|self.reg(r2)?))
+    fn alu_BCLR_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //clear a bit
+        let x = self.bclr(self.reg(r1)?, v);
+        self.set(0, x)
     }
 
-    fn alu_AND_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?&v)
+    fn alu_BCLR_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.bclr(self.reg(r1)?, self.reg(r2)?);
+        self.set(0, x)
     }
 
-    fn alu_AND_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, self.reg(r1)?&self.reg(r2)?)
+    fn alu_BTST_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //@0 is 1 if the bit is set; zero flag if it is clear
+        let x = self.logic(self.reg(r1)?>>(v&7)&1);
+        self.set(0, x)
     }
 
-    fn cmp_GRT_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?>v))
+    fn alu_BTST_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.logic(self.reg(r1)?>>(self.reg(r2)?&7)&1);
+        self.set(0, x)
     }
 
-    fn cmp_GRT_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?>self.reg(r2)?))
+    fn alu_POPC_R(&mut self, r: u8) -> Result<(), Fault> { //count the bits set in @r
+        let x = self.logic(self.reg(r)?.count_ones() as u8);
+        self.set(0, x)
     }
 
-    fn cmp_LST_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?<v))
+    fn alu_MUL_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> { //low byte to @0, high byte to @1
+        self.mul(self.reg(r1)?, v)
     }
 
-    fn cmp_LST_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?<self.reg(r2)?))
+    fn alu_MUL_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        self.mul(self.reg(r1)?, self.reg(r2)?)
     }
 
-    fn cmp_GREQT_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?>=v))
+    fn alu_DIV_RV(&mut self, r: u8, v: u8) -> Result<(), Fault> {
+        let x = self.div(self.reg(r)?, v)?.0;
+        self.set(0, x)
     }
 
-    fn cmp_GREQT_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?>=self.reg(r2)?))
+    fn alu_DIV_VR(&mut self, v: u8, r: u8) -> Result<(), Fault> {
+        let x = self.div(v, self.reg(r)?)?.0;
+        self.set(0, x)
     }
 
-    fn cmp_LSEQT_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?<=v))
+    fn alu_DIV_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.div(self.reg(r1)?, self.reg(r2)?)?.0;
+        self.set(0, x)
     }
 
-    fn cmp_LSEQT_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?<=self.reg(r2)?))
+    fn alu_MOD_RV(&mut self, r: u8, v: u8) -> Result<(), Fault> {
+        let x = self.div(self.reg(r)?, v)?.1;
+        self.set(0, x)
     }
 
-    fn cmp_EQL_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?==v))
+    fn alu_MOD_VR(&mut self, v: u8, r: u8) -> Result<(), Fault> {
+        let x = self.div(v, self.reg(r)?)?.1;
+        self.set(0, x)
     }
 
-    fn cmp_EQL_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?==self.reg(r2)?))
+    fn alu_MOD_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let x = self.div(self.reg(r1)?, self.reg(r2)?)?.1;
+        self.set(0, x)
     }
 
-    fn cmp_NEQL_RV(&mut self, r1: u8, v: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?!=v))
+    fn cmp_GRT_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a>b))
     }
 
-    fn cmp_NEQL_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> {
-        self.set(0, u8ify(self.reg(r1)?!=self.reg(r2)?))
+    fn cmp_GRT_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a>b))
     }
 
-    fn goto_ZRO_DRI(&mut self, r1: u8, i: u8, d: u8) -> Result<(), &'static str> { //goto i if @r1 is 0
+    fn cmp_LST_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a<b))
+    }
+
+    fn cmp_LST_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a<b))
+    }
+
+    fn cmp_GREQT_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a>=b))
+    }
+
+    fn cmp_GREQT_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a>=b))
+    }
+
+    fn cmp_LSEQT_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a<=b))
+    }
+
+    fn cmp_LSEQT_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a<=b))
+    }
+
+    fn cmp_EQL_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a==b))
+    }
+
+    fn cmp_EQL_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a==b))
+    }
+
+    fn cmp_NEQL_RV(&mut self, r1: u8, v: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, v);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a!=b))
+    }
+
+    fn cmp_NEQL_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> {
+        let (a, b) = (self.reg(r1)?, self.reg(r2)?);
+        self.sub(a, b, 0);
+        self.set(0, u8ify(a!=b))
+    }
+
+    fn goto_ZRO_DRI(&mut self, r1: u8, i: u8, d: u8) -> Result<(), Fault> { //goto i if @r1 is 0
         if (self.reg(r1)?==0) {self.goto(i, d)?};
         Ok(())
     }
 
-    fn goto_ZRO_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), &'static str> { //goto @r2 if @r1 is 0
+    fn goto_ZRO_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), Fault> { //goto @r2 if @r1 is 0
         if (self.reg(r1)?==0) {self.goto(self.reg(r2)?, d)?};
         Ok(())
     }
 
-    fn goto_ZRO_RRI(&mut self, r1: u8, i: u8, r2: u8) -> Result<(), &'static str> { //goto i if @r1 is 0
+    fn goto_ZRO_RRI(&mut self, r1: u8, i: u8, r2: u8) -> Result<(), Fault> { //goto i if @r1 is 0
         if (self.reg(r1)?==0) {self.goto(i, self.reg(r2)?)?};
         Ok(())
     }
 
-    fn goto_ZRO_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), &'static str> { //goto @r2 if @r1 is 0
+    fn goto_ZRO_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), Fault> { //goto @r2 if @r1 is 0
         if (self.reg(r1)?==0) {self.goto(self.reg(r2)?, self.reg(r3)?)?};
         Ok(())
     }
 
-    fn goto_NZRO_DRI(&mut self, r1: u8, i: u8, d: u8) -> Result<(), &'static str> { //goto i if @r1 is 0
+    fn goto_NZRO_DRI(&mut self, r1: u8, i: u8, d: u8) -> Result<(), Fault> { //goto i if @r1 is 0
         if (self.reg(r1)?!=0) {self.goto(i, d)?};
         Ok(())
     }
 
-    fn goto_NZRO_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), &'static str> { //goto @r2 if @r1 is 0
+    fn goto_NZRO_DRR(&mut self, r1: u8, r2: u8, d: u8) -> Result<(), Fault> { //goto @r2 if @r1 is 0
         if (self.reg(r1)?!=0) {self.goto(self.reg(r2)?, d)?};
         Ok(())
     }
 
-    fn goto_NZRO_RRI(&mut self, r1: u8, i: u8, r2: u8) -> Result<(), &'static str> { //goto i if @r1 is 0
+    fn goto_NZRO_RRI(&mut self, r1: u8, i: u8, r2: u8) -> Result<(), Fault> { //goto i if @r1 is 0
         if (self.reg(r1)?!=0) {self.goto(i, self.reg(r2)?)?};
         Ok(())
     }
 
-    fn goto_NZRO_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), &'static str> { //goto @r2 if @r1 is 0
+    fn goto_NZRO_RRR(&mut self, r1: u8, r2: u8, r3: u8) -> Result<(), Fault> { //goto @r2 if @r1 is 0
         if (self.reg(r1)?!=0) {self.goto(self.reg(r3)?, self.reg(r2)?)?};
         Ok(())
     }
 
-    fn goto_UNCON_DI(&mut self, i: u8, d: u8) -> Result<(), &'static str> { //goto i
+    fn goto_UNCON_DI(&mut self, i: u8, d: u8) -> Result<(), Fault> { //goto i
         self.goto(i, d)
     }
 
-    fn goto_UNCON_DR(&mut self, r: u8, d: u8) -> Result<(), &'static str> { //goto @r
+    fn goto_UNCON_DR(&mut self, r: u8, d: u8) -> Result<(), Fault> { //goto @r
         self.goto(self.reg(r)?, d)
     }
 
-    fn goto_UNCON_RI(&mut self, i: u8, r: u8) -> Result<(), &'static str> { //goto i
+    fn goto_UNCON_RI(&mut self, i: u8, r: u8) -> Result<(), Fault> { //goto i
         self.goto(i, self.reg(r)?)
     }
 
-    fn goto_UNCON_RR(&mut self, r1: u8, r2: u8) -> Result<(), &'static str> { //goto @r
+    fn goto_UNCON_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> { //goto @r
         self.goto(self.reg(r1)?, self.reg(r2)?)
     }
 
-    fn end_END(&mut self) -> Result<(), &'static str> {
+    fn goto_FLAG_DI(&mut self, i: u8, d: u8, flag: u8, set: bool) -> Result<(), Fault> { //goto i if the flag is (or isn't) set
+        if (self.flags&flag != 0) == set {self.goto(i, d)?};
+        Ok(())
+    }
+
+    fn stk_CALL_DI(&mut self, i: u8, d: u8) -> Result<(), Fault> { //push the return address, then goto i
+        self.call(i, d)
+    }
+
+    fn stk_CALL_DR(&mut self, r: u8, d: u8) -> Result<(), Fault> { //push the return address, then goto @r
+        self.call(self.reg(r)?, d)
+    }
+
+    fn stk_RET(&mut self) -> Result<(), Fault> { //goto the address the last CALL pushed
+        let d = self.pop()?;
+        let i = self.pop()?;
+        self.goto(i, d)
+    }
+
+    fn stk_PUSH_R(&mut self, r: u8) -> Result<(), Fault> {
+        let v = self.reg(r)?;
+        self.push(v)
+    }
+
+    fn stk_POP_R(&mut self, r: u8) -> Result<(), Fault> {
+        self.reg(r)?;
+        let v = self.pop()?;
+        self.set(r, v)
+    }
+
+    fn trap_RETT(&mut self) -> Result<(), Fault> { //leave a fault handler, carrying on after the instruction that faulted with @0 as it was
+        let frame = self.trap_frame.ok_or(Fault::IllegalInstruction(0b11110000))?;
+        let (d, i) = frame.resume.ok_or(Fault::FetchPastEnd)?; //the fault ran off the end of a disc, so there is nothing to carry on at
+        self.set(0, frame.saved)?;
+        self.trap_frame = None;
+        self.goto(i, d)
+    }
+
+    fn trap_TRAP_DI(&mut self, i: u8, d: u8) -> Result<(), Fault> { //send faults to i
+        self.trap_vector = Some((self.getdsc(d)?, i));
+        Ok(())
+    }
+
+    fn trap_TLOC_RR(&mut self, r1: u8, r2: u8) -> Result<(), Fault> { //where the fault being handled happened: disc to @r1, pc to @r2
+        let frame = self.trap_frame.ok_or(Fault::IllegalInstruction(0b11110010))?;
+        self.reg(r2)?;
+        self.set(r1, frame.disc)?;
+        self.set(r2, frame.pc)
+    }
+
+    fn end_END(&mut self, v: u8) -> Result<(), Fault> {
         self.isactive = false;
+        self.status = v;
         Ok(())
     }
 
     //non-instructions
-    fn mem(&self, d: u8, i: u8) -> Result<u8, &'static str> { //return val at index
-        let d = self.getdsc(d)? as usize;
-        if {d < DISCS} {
-            Ok(self.mem[d][i as usize])
-        } else {
-            Err("Failed to set memory: Disc does not exist.")
+    fn add(&mut self, a: u8, b: u8, c: u8) -> u8 { //a+b+c, setting flags
+        let wide = a as u16 + b as u16 + c as u16;
+        let x = wide as u8;
+        self.flags = zn(x) | if wide > 0xff {FLAG_CARRY} else {0} | if (a^x)&(b^x)&0x80 != 0 {FLAG_OVERFLOW} else {0};
+        x
+    }
+
+    fn sub(&mut self, a: u8, b: u8, c: u8) -> u8 { //a-b-c, setting flags
+        let x = a.wrapping_sub(b).wrapping_sub(c);
+        self.flags = zn(x) | if (a as u16) < b as u16 + c as u16 {FLAG_CARRY} else {0} | if (a^b)&(a^x)&0x80 != 0 {FLAG_OVERFLOW} else {0};
+        x
+    }
+
+    fn logic(&mut self, x: u8) -> u8 { //bitwise results clear carry and overflow
+        self.flags = zn(x);
+        x
+    }
+
+    fn shl(&mut self, a: u8, n: u8) -> u8 { //shifting by 8 or more leaves nothing
+        let wide = (a as u32) << n.min(9);
+        self.flags = zn(wide as u8) | if wide&0x100 != 0 {FLAG_CARRY} else {0};
+        wide as u8
+    }
+
+    fn shr(&mut self, a: u8, n: u8) -> u8 {
+        let wide = ((a as u32) << 1) >> n.min(9); //keep the last bit out below the result
+        let x = (wide >> 1) as u8;
+        self.flags = zn(x) | if wide&1 != 0 {FLAG_CARRY} else {0};
+        x
+    }
+
+    fn rol(&mut self, a: u8, n: u8) -> u8 { //carry is the bit that went round last
+        let x = a.rotate_left((n&7) as u32);
+        self.flags = zn(x) | if n&7 != 0 && x&1 != 0 {FLAG_CARRY} else {0};
+        x
+    }
+
+    fn ror(&mut self, a: u8, n: u8) -> u8 {
+        let x = a.rotate_right((n&7) as u32);
+        self.flags = zn(x) | if n&7 != 0 && x&0x80 != 0 {FLAG_CARRY} else {0};
+        x
+    }
+
+    fn bset(&mut self, a: u8, n: u8) -> u8 { //bit numbers wrap at 8
+        self.logic(a | 1<<(n&7))
+    }
+
+    fn bclr(&mut self, a: u8, n: u8) -> u8 {
+        self.logic(a & !(1<<(n&7)))
+    }
+
+    fn mul(&mut self, a: u8, b: u8) -> Result<(), Fault> { //carry if the product needed the high byte
+        let wide = a as u16 * b as u16;
+        self.flags = zn(wide as u8) | if wide > 0xff {FLAG_CARRY} else {0};
+        self.set(0, wide as u8)?;
+        self.set(1, (wide >> 8) as u8)
+    }
+
+    fn div(&mut self, a: u8, b: u8) -> Result<(u8, u8), Fault> { //quotient and remainder, setting flags by the quotient
+        if b == 0 {
+            return Err(Fault::DivideByZero);
         }
+        self.logic(a/b);
+        Ok((a/b, a%b))
     }
 
-    fn sav(&mut self, d: u8, i: u8, v: u8) -> Result<(), &'static str> { //save at index
-        let d = self.getdsc(d)? as usize;
-        if {d < DISCS} {
-            self.mem[d][i as usize] = v;
+    fn carry(&self) -> u8 {
+        u8ify(self.flags&FLAG_CARRY != 0)
+    }
+
+    fn call(&mut self, i: u8, d: u8) -> Result<(), Fault> {
+        self.getdsc(d)?;
+        let (rd, ri) = self.locate(self.dsccount, self.prgcount, 3)?; //return to the next instruction
+        self.push(ri)?;
+        self.push(rd)?;
+        self.goto(i, d)
+    }
+
+    fn push(&mut self, v: u8) -> Result<(), Fault> { //the last byte of the stack disc is never used, so sp always fits in a byte
+        if self.sp == 255 {
+            return Err(Fault::StackOverflow);
+        }
+        self.sav(self.stack_disc, self.sp, v)?;
+        self.sp += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u8, Fault> {
+        if self.sp == 0 {
+            return Err(Fault::StackUnderflow);
+        }
+        self.sp -= 1;
+        self.mem(self.stack_disc, self.sp)
+    }
+
+    fn trap(&mut self, fault: Fault) -> Result<(), Fault> { //hand a fault to the program's handler, if it has one that isn't already busy
+        let (d, i) = match (self.trap_vector, self.trap_frame, &fault) {
+            (_, _, Fault::Io(_)) => return Err(fault), //the host's problem, not the program's
+            (Some(vector), None, _) => vector,
+            _ => return Err(fault),
+        };
+        let resume = if self.past_end {None} else {self.locate(self.dsccount, self.prgcount, 3).ok()}; //after the instruction that faulted
+        self.trap_frame = Some(TrapFrame { disc: self.dsccount, pc: self.prgcount, saved: self.reg(0)?, resume });
+        self.set(0, fault.code())?;
+        self.dsccount = d;
+        self.prgcount = i;
+        self.past_end = false;
+        self.jmp = false; //the faulting instruction never reached next_ins
+        Ok(())
+    }
+
+    fn recording(&self) -> bool { //whether anyone needs to know what each instruction wrote
+        self.tracer.is_some() || self.journaling()
+    }
+
+    fn allow(&self, d: u8, i: u8, access: Access) -> Result<(), Fault> { //check the disc's permissions
+        if self.permissions[self.getdsc(d)? as usize].allows(access) {
             Ok(())
         } else {
-            Err("Failed to set memory: Disc does not exist.")
+            Err(Fault::AccessDenied { disc: d, index: i, access })
         }
     }
 
-    fn reg(&self, r: u8) -> Result<u8, &'static str> { //return val at register
-        if {(r as usize) < REGISTERS} {
+    fn peek(&self, d: u8, i: u8) -> Result<u8, Fault> { //return val at index, without involving devices
+        let d = self.getdsc(d)? as usize;
+        Ok(self.mem[d][i as usize])
+    }
+
+    fn mem(&mut self, d: u8, i: u8) -> Result<u8, Fault> { //return val at index
+        let d = self.getdsc(d)?;
+        self.allow(d, i, Access::Read)?;
+        for (n, device) in self.devices.iter_mut() {
+            if *n == d {
+                device.on_read(&mut self.mem[d as usize], i)?;
+            }
+        }
+        let v = self.mem[d as usize][i as usize];
+        if self.mem_watches.iter().any(|w| w.covers(d, i, Access::Read)) {
+            self.hit.get_or_insert(Hit::Memory { disc: d, index: i, access: Access::Read, value: v });
+        }
+        Ok(v)
+    }
+
+    fn sav(&mut self, d: u8, i: u8, v: u8) -> Result<(), Fault> { //save at index
+        let d = self.getdsc(d)?;
+        self.allow(d, i, Access::Write)?;
+        if let Some(&instruction) = self.fetched.get(&(d, i)) {
+            let write = CodeWrite { writer: (self.dsccount, self.prgcount), target: (d, i), instruction };
+            match self.self_modify {
+                SelfModify::Fault => return Err(Fault::CodeWritten(write)),
+                _ if self.code_writes.contains(&write) => {}, //a loop doing it again isn't news
+                _ => self.code_writes.push(write),
+            }
+        }
+        if self.recording() {
+            self.mem_writes.push(MemoryWrite { disc: d, index: i, old: self.mem[d as usize][i as usize], new: v });
+        }
+        self.mem[d as usize][i as usize] = v;
+        for (n, device) in self.devices.iter_mut() {
+            if *n == d {
+                device.on_write(&mut self.mem[d as usize], i)?;
+            }
+        }
+        self.persist(d, i, v)?;
+        if self.mem_watches.iter().any(|w| w.covers(d, i, Access::Write)) {
+            self.hit.get_or_insert(Hit::Memory { disc: d, index: i, access: Access::Write, value: v });
+        }
+        Ok(())
+    }
+
+    fn reg(&self, r: u8) -> Result<u8, Fault> { //return val at register
+        if (r as usize) < self.registers.len() {
             Ok(self.registers[r as usize])
         } else {
-            Err("Failed to get register: Register does not exist.")
+            Err(Fault::BadRegister(r))
         }
     }
 
-    fn set(&mut self, r: u8, v: u8) -> Result<(), &'static str> { //set register
-        if {(r as usize) < REGISTERS} {
+    fn set(&mut self, r: u8, v: u8) -> Result<(), Fault> { //set register
+        if (r as usize) < self.registers.len() {
+            let old = self.registers[r as usize];
+            if self.recording() {
+                self.reg_writes.push(RegisterWrite { register: r, old, new: v });
+            }
             self.registers[r as usize] = v;
+            if old != v && self.reg_watches.contains(&r) {
+                self.hit.get_or_insert(Hit::Register { register: r, old, new: v });
+            }
             Ok(())
         } else {
-            Err("Failed to set register: Register does not exist.")
+            Err(Fault::BadRegister(r))
         }
     }
 
-    fn pos(&mut self) -> Result<u8, &'static str> { //get current program pos (byte; not word)
+    fn pos(&mut self) -> Result<u8, Fault> { //get current program pos (byte; not word)
         Ok(self.prgcount)
     }
 
-    fn goto(&mut self, i: u8, d: u8) -> Result<(), &'static str> { //set program pos (byte; not word) & disc
+    fn goto(&mut self, i: u8, d: u8) -> Result<(), Fault> { //set program pos (byte; not word) & disc
+        self.dsccount = self.getdsc(d)?;
         self.jmp = true;
         self.prgcount = i;
-        self.dsccount = self.getdsc(d)?;
         Ok(())
     }
 
-    fn getdsc(&self, d: u8) -> Result<u8, &'static str> { //get disc
-        if {d > 7} {Err("Fatal Error: Disc does not exist.")} else {Ok(d)}
+    fn getdsc(&self, d: u8) -> Result<u8, Fault> { //get disc
+        if d as usize >= self.mem.len() {Err(Fault::BadDisc(d))} else {Ok(d)}
     }
 
-    fn loaddsc(&mut self, d: u8, f: [u8; 256]) -> Result<(), &'static str> {
-        self.mem[self.getdsc(d)? as usize] = f;
-        Ok(())
-    }
-
-    fn next_ins(&mut self) -> Result<(), &'static str> {
-        if {self.jmp} {
+    fn next_ins(&mut self) {
+        if self.jmp {
             self.jmp = false;
-            Ok(())
         } else {
-            self.prgcount = (Wrapping(self.prgcount)+Wrapping(3)).0;
-            Ok(())
+            match self.locate(self.dsccount, self.prgcount, 3) {
+                Ok(x) => (self.dsccount, self.prgcount) = x,
+                Err(_) => self.past_end = true, //this instruction ran fine; fetching the next is what fails
+            }
         }
     }
 
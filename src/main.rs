@@ -1,71 +1,371 @@
-#![feature(duration_as_u128)]
 #![allow(unused_variables)]
 #[macro_use] extern crate lazy_static;
 extern crate risc_vm;
 mod format;
 mod compile;
+mod debugger;
 use std::fs;
+use std::io::{BufReader, BufWriter, Write};
+use risc_vm::trace;
 use std::time::Instant;
 
-use std::{process, env, path::Path};
+use std::{process, env, path::Path, ffi::OsStr};
+
+//exit codes: 0 to MAX_STATUS are the program's own END status, and everything above is ours:
+//201 bad arguments, 202 unrecognised file, 203 fault, 204 unknown command, 205 nowhere to compile to,
+//206 filesystem error, 207 compile error, 208 budget exhausted, 209 bad snapshot,
+//210 undefined opcode, 211 illegal instruction (--strict), 212 load error, 213 status above MAX_STATUS
+pub const MAX_STATUS: u8 = 199;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 3 {
         println!("Argument ERROR: Not enough arguments supplied.");
-        process::exit(1);
+        process::exit(201);
     }
     let command = &args[1];
     let extension = Path::new(&args[2]).extension().unwrap_or_else(|| {
         println!("Filetype ERROR: Could not infer filetype.");
-        process::exit(2);
+        process::exit(202);
     });
     if command == "run" {
-        if extension == "red" {
-            let start = Instant::now();
-            risc_vm::run(format::debug(&args[2])).unwrap_or_else(|err| {
-                println!("Application ERROR: {}", err);
-                process::exit(3);
-            });
-            println!("\nFIN: Program ran for {}ms.", start.elapsed().as_millis())
-        } else if extension == "rex" {
-            let start = Instant::now();
-            risc_vm::run(format::bytes(&args[2])).unwrap_or_else(|err| {
-                println!("Application ERROR: {}", err);
-                process::exit(3);
-            });
-            println!("\nFIN: Program ran for {}ms.", start.elapsed().as_millis())
-        } else {
+        let program = load(&args[2], extension);
+        let mut vm = risc_vm::boot_with(machine(&args), &program).unwrap_or_else(|err| boot_failed(&err));
+        preload(&mut vm, &program, &args);
+        persist(&mut vm, &program, &args);
+        protect(&mut vm, &program, &args);
+        execute(vm, &args);
+    } else if command == "resume" {
+        if extension != "rsnap" {
             println!("Filetype ERROR: Did not recognise filetype.");
-            process::exit(2);
+            process::exit(202);
         }
+        let mut vm = risc_vm::boot_with(machine(&args), &[]).unwrap_or_else(|err| boot_failed(&err));
+        persist(&mut vm, &[], &args); //the snapshot's copy of the disc wins, but flushes still go to the file
+        let mut file = BufReader::new(fs::File::open(&args[2]).unwrap_or_else(|err| {
+            println!("Argument ERROR: {}", err);
+            process::exit(201);
+        }));
+        vm.restore_state(&mut file).unwrap_or_else(|err| {
+            println!("Snapshot ERROR: {}", err);
+            process::exit(209);
+        });
+        execute(vm, &args);
+    } else if command == "debug" {
+        let program = load(&args[2], extension);
+        let mut vm = risc_vm::boot_with(machine(&args), &program).unwrap_or_else(|err| boot_failed(&err));
+        preload(&mut vm, &program, &args);
+        persist(&mut vm, &program, &args);
+        protect(&mut vm, &program, &args);
+        configure(&mut vm, &args);
+        let vm = debugger::repl(vm);
+        flush(&vm);
     } else if command == "compile" {
         if extension == "rasm" {
             if args.len() < 4 {
                 println!("Argument ERROR: Missing location to compile to.");
-                process::exit(5);
+                process::exit(205);
             } else {
                 let location = &args[3];
                 let extension = Path::new(&args[3]).extension().unwrap_or_else(|| {
                     println!("Filetype ERROR: Could not infer filetype.");
-                    process::exit(2);
+                    process::exit(202);
                 });
                 if extension == "rex" {
-                    fs::write(location, compile::rasm(&args[2]).unwrap_or_else(|err| {
+                    fs::write(location, compile::rasm(&args[2], &config(&args)).unwrap_or_else(|err| {
                         println!("Compilation ERROR: {}", err);
-                        process::exit(7);
+                        process::exit(207);
                     })).unwrap_or_else(|err| {
                         println!("Filesystem ERROR: {}", err);
-                        process::exit(6);
+                        process::exit(206);
                     });
                 } else {
                     println!("Filetype ERROR: Did not recognise filetype.");
-                    process::exit(2);
+                    process::exit(202);
                 }
             }
         }
     } else {
         println!("Command ERROR: {:?} is not a valid command", command);
-        process::exit(4)
+        process::exit(204)
+    }
+}
+
+fn execute(mut vm: risc_vm::Machine, args: &[String]) { //run a booted machine as the options ask, and report how it went
+    configure(&mut vm, args);
+    vm.set_budget(risc_vm::Budget { steps: number(args, "--max-steps"), cycles: number(args, "--max-cycles") });
+    if let Some(path) = option(args, "--trace") {
+        vm.set_tracer(Some(tracer(path)));
+    }
+    let start = Instant::now();
+    let result = vm.run();
+    vm.set_tracer(None); //flushes the trace before we might exit
+    flush(&vm); //even after a fault, so the program's state isn't lost
+    for write in vm.code_writes() {
+        println!("\nWARNING: Self-modifying code: {}.", write);
+    }
+    if let Some(path) = option(args, "--save-state") {
+        save(&vm, path);
+    }
+    let stop = result.unwrap_or_else(|err| {
+        fault(&err);
+        if let risc_vm::Fault::IllegalInstruction(_) = err.fault {
+            process::exit(211);
+        }
+        process::exit(203);
+    });
+    let outcome = vm.outcome(stop);
+    match outcome.stop {
+        risc_vm::Stop::Halted(status) => {
+            println!("\nFIN: Program ran for {}ms ({} instructions, {} cycles).", start.elapsed().as_millis(), outcome.steps, outcome.cycles);
+            if status > MAX_STATUS {
+                println!("FIN: Program ended with status {}, but only statuses up to {} can be passed on.", status, MAX_STATUS);
+                process::exit(213);
+            }
+            if status != 0 {
+                println!("FIN: Program ended with status {}.", status);
+                process::exit(status as i32);
+            }
+        },
+        risc_vm::Stop::BudgetExhausted => {
+            println!("\nTIMEOUT: Budget exhausted after {} instructions ({} cycles) at disc {}, pc {}.", outcome.steps, outcome.cycles, outcome.disc, outcome.pc);
+            println!("    registers: {:?}", outcome.registers);
+            process::exit(208);
+        },
+        risc_vm::Stop::UndefinedOpcode { .. } => {
+            println!("\nFIN: Program stopped: {}.", outcome.stop);
+            process::exit(210);
+        },
+        stop => println!("\nFIN: Program stopped: {}.", stop),
+    }
+}
+
+fn configure(vm: &mut risc_vm::Machine, args: &[String]) { //options shared by everything that runs a program
+    vm.set_strict(args.iter().any(|a| a == "--strict"));
+    if let Some(policy) = option(args, "--fetch") {
+        vm.set_fetch_policy(match policy.as_str() {
+            "next-disc" => risc_vm::FetchPolicy::NextDisc,
+            "wrap" => risc_vm::FetchPolicy::Wrap,
+            "fault" => risc_vm::FetchPolicy::Fault,
+            _ => {
+                println!("Argument ERROR: --fetch expects next-disc, wrap or fault, not {:?}.", policy);
+                process::exit(201);
+            },
+        });
+    }
+    if let Some(mode) = option(args, "--self-modify") {
+        vm.set_self_modify(match mode.as_str() {
+            "allow" => risc_vm::SelfModify::Allow,
+            "report" => risc_vm::SelfModify::Report,
+            "fault" => risc_vm::SelfModify::Fault,
+            _ => {
+                println!("Argument ERROR: --self-modify expects allow, report or fault, not {:?}.", mode);
+                process::exit(201);
+            },
+        });
+    }
+}
+
+fn protect(vm: &mut risc_vm::Machine, program: &[[u8; 256]], args: &[String]) { //--protect-code: the discs the program was loaded onto become read-only
+    if !args.iter().any(|a| a == "--protect-code") {
+        return;
     }
+    for d in vm.config().program_discs.clone().into_iter().take(program.len()) {
+        vm.set_permissions(d, risc_vm::Permissions::CODE).unwrap();
+    }
+}
+
+fn boot_failed(err: &risc_vm::LoadError) -> ! {
+    println!("Load ERROR: {}", err);
+    process::exit(212);
+}
+
+fn preload(vm: &mut risc_vm::Machine, program: &[[u8; 256]], args: &[String]) { //--disc N=path, as often as wanted
+    let used = vm.config().program_discs[..program.len()].to_vec();
+    for value in args.windows(2).filter(|w| w[0] == "--disc").map(|w| &w[1]) {
+        let (d, path) = match value.split_once('=').map(|(d, path)| (d.parse::<u8>(), path)) {
+            Some((Ok(d), path)) => (d, path.to_string()),
+            _ => {
+                println!("Argument ERROR: --disc expects N=path, not {:?}.", value);
+                process::exit(201);
+            },
+        };
+        if used.contains(&d) {
+            println!("Argument ERROR: Disc {} already has part of the program on it.", d);
+            process::exit(201);
+        }
+        let disc = match Path::new(&path).extension() {
+            Some(x) if x == "red" || x == "rex" => {
+                let discs = load(&path, x);
+                if discs.len() != 1 {
+                    println!("Load ERROR: {} holds {} discs, not 1.", path, discs.len());
+                    process::exit(212);
+                }
+                discs[0]
+            },
+            _ => {
+                let bytes = fs::read(&path).unwrap_or_else(|err| {
+                    println!("Argument ERROR: {}", err);
+                    process::exit(201);
+                });
+                if bytes.len() > 256 {
+                    println!("Load ERROR: {} is {} bytes, more than fit on a disc.", path, bytes.len());
+                    process::exit(212);
+                }
+                let mut disc = [0; 256];
+                disc[..bytes.len()].copy_from_slice(&bytes);
+                disc
+            },
+        };
+        vm.load_disc(d, disc).unwrap_or_else(|err| boot_failed(&err));
+    }
+}
+
+fn persist(vm: &mut risc_vm::Machine, program: &[[u8; 256]], args: &[String]) { //--persist N=path: a disc kept in a host file between runs
+    let used = vm.config().program_discs[..program.len()].to_vec();
+    let preloaded: Vec<&String> = args.windows(2).filter(|w| w[0] == "--disc").map(|w| &w[1]).collect();
+    for value in args.windows(2).filter(|w| w[0] == "--persist").map(|w| &w[1]) {
+        let (d, path) = match value.split_once('=').map(|(d, path)| (d.parse::<u8>(), path)) {
+            Some((Ok(d), path)) => (d, path),
+            _ => {
+                println!("Argument ERROR: --persist expects N=path, not {:?}.", value);
+                process::exit(201);
+            },
+        };
+        if used.contains(&d) {
+            println!("Argument ERROR: Disc {} already has part of the program on it.", d);
+            process::exit(201);
+        }
+        if preloaded.iter().any(|v| v.split_once('=').is_some_and(|(e, _)| e.parse() == Ok(d))) {
+            println!("Argument ERROR: Disc {} is given by both --disc and --persist.", d);
+            process::exit(201);
+        }
+        vm.back_disc(d, path).unwrap_or_else(|err| {
+            println!("Load ERROR: {}", err);
+            process::exit(212);
+        });
+    }
+    vm.set_flush_interval(number(args, "--flush-every"));
+}
+
+fn flush(vm: &risc_vm::Machine) {
+    vm.flush_discs().unwrap_or_else(|err| {
+        println!("Filesystem ERROR: {}", err);
+        process::exit(206);
+    });
+}
+
+fn config(args: &[String]) -> risc_vm::MachineConfig { //the standard machine, resized by --discs and --registers, loading programs onto the discs in --map (or every free one), with stdin on disc 5 if --input
+    let mut config = risc_vm::MachineConfig::default(); //--stack-disc, --console-disc and --input-disc move things out of the way of a smaller machine
+    if let Some(n) = number(args, "--discs") {
+        config.discs = n as usize;
+    }
+    if let Some(n) = number(args, "--registers") {
+        config.registers = n as usize;
+    }
+    if let Some(mode) = option(args, "--input") {
+        let mode = match mode.as_str() {
+            "blocking" => risc_vm::ReadMode::Blocking,
+            "non-blocking" => risc_vm::ReadMode::NonBlocking,
+            _ => {
+                println!("Argument ERROR: --input expects blocking or non-blocking, not {:?}.", mode);
+                process::exit(201);
+            },
+        };
+        config.devices.push((disc(args, "--input-disc").unwrap_or(risc_vm::INPUT_DISC), risc_vm::StandardDevice::Input(mode)));
+    }
+    if let Some(d) = disc(args, "--stack-disc") {
+        config.stack_disc = d;
+    }
+    if option(args, "--console-disc").is_some_and(|c| c == "none") {
+        config.devices.retain(|(_, device)| *device != risc_vm::StandardDevice::Console);
+    } else if let Some(d) = disc(args, "--console-disc") {
+        for (at, device) in config.devices.iter_mut() {
+            if *device == risc_vm::StandardDevice::Console {
+                *at = d;
+            }
+        }
+    }
+    config.program_discs = match option(args, "--map") {
+        Some(map) => map.split(',').map(|d| d.trim().parse()).collect::<Result<_, _>>().unwrap_or_else(|_| {
+            println!("Argument ERROR: --map expects disc numbers separated by commas, not {:?}.", map);
+            process::exit(201);
+        }),
+        None => config.free_discs(), //now the devices and the stack have been put where they are going
+    };
+    config
+}
+
+fn machine(args: &[String]) -> risc_vm::Machine {
+    risc_vm::Machine::with_config(config(args)).unwrap_or_else(|err| {
+        println!("Argument ERROR: {}", err);
+        process::exit(201);
+    })
+}
+
+fn save(vm: &risc_vm::Machine, path: &String) {
+    let mut file = BufWriter::new(fs::File::create(path).unwrap_or_else(|err| {
+        println!("Filesystem ERROR: {}", err);
+        process::exit(206);
+    }));
+    vm.save_state(&mut file).and_then(|_| file.flush()).unwrap_or_else(|err| {
+        println!("Filesystem ERROR: {}", err);
+        process::exit(206);
+    });
+}
+
+fn load(path: &String, extension: &OsStr) -> Vec<[u8; 256]> {
+    if extension == "red" {
+        format::debug(path)
+    } else if extension == "rex" {
+        format::bytes(path)
+    } else {
+        println!("Filetype ERROR: Did not recognise filetype.");
+        process::exit(202);
+    }
+}
+
+fn tracer(path: &String) -> Box<dyn risc_vm::Tracer> { //csv for .csv files, JSON lines for anything else
+    let file = BufWriter::new(fs::File::create(path).unwrap_or_else(|err| {
+        println!("Filesystem ERROR: {}", err);
+        process::exit(206);
+    }));
+    if Path::new(path).extension().is_some_and(|x| x == "csv") {
+        Box::new(trace::Csv::new(file))
+    } else {
+        Box::new(trace::JsonLines::new(file))
+    }
+}
+
+fn fault(err: &risc_vm::VmError) {
+    println!("\nApplication ERROR: {}", err.fault);
+    println!("    at disc {}, pc {}: {:08b} {:08b} {:08b}", err.disc, err.pc, err.ins[0], err.ins[1], err.ins[2]);
+    println!("    registers: {:?}", err.registers);
+}
+
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a String> { //value following a --flag
+    let i = args.iter().position(|a| a == name)?;
+    match args.get(i+1) {
+        Some(x) => Some(x),
+        None => {
+            println!("Argument ERROR: Missing value for {}.", name);
+            process::exit(201);
+        }
+    }
+}
+
+fn disc(args: &[String], name: &str) -> Option<u8> {
+    number(args, name).map(|d| if d > 255 {
+        println!("Argument ERROR: There is no disc {} for {}.", d, name);
+        process::exit(201);
+    } else {
+        d as u8
+    })
+}
+
+fn number(args: &[String], name: &str) -> Option<u64> {
+    option(args, name).map(|x| x.parse().unwrap_or_else(|_| {
+        println!("Argument ERROR: {} expects a number, not {:?}.", name, x);
+        process::exit(201);
+    }))
 }
@@ -0,0 +1,68 @@
+use std::{fs, io};
+use std::path::{Path, PathBuf};
+use crate::{Fault, Machine};
+
+//discs backed by files on the host, so what a program leaves on them survives between runs.
+//writing anything but 0 to index 254 of a backed disc saves it straight away; the byte reads back as 0.
+//not 255, which a .rex program has no way to name
+pub const FLUSH_INDEX: u8 = 254;
+
+impl Machine {
+    pub fn back_disc<P: AsRef<Path>>(&mut self, d: u8, path: P) -> io::Result<()> { //load the disc from the file, if there is one, and save it there on flush
+        let path = path.as_ref().to_path_buf();
+        let d = self.getdsc(d).map_err(|f| io::Error::new(io::ErrorKind::InvalidInput, f.to_string()))?;
+        match fs::read(&path) {
+            Ok(bytes) if bytes.len() > 256 => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is {} bytes, more than fit on a disc", path.display(), bytes.len())));
+            },
+            Ok(bytes) => {
+                let mut disc = [0; 256];
+                disc[..bytes.len()].copy_from_slice(&bytes);
+                self.mem[d as usize] = disc;
+                self.forget_fetched(d);
+            },
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}, //created on the first flush
+            Err(err) => return Err(err),
+        }
+        self.backing.retain(|(n, _)| *n != d);
+        self.backing.push((d, path));
+        Ok(())
+    }
+
+    pub fn backed_discs(&self) -> &[(u8, PathBuf)] {
+        &self.backing
+    }
+
+    pub fn set_flush_interval(&mut self, steps: Option<u64>) { //also flush every so many instructions
+        self.flush_every = steps.filter(|n| *n > 0);
+    }
+
+    pub fn flush_discs(&self) -> io::Result<()> { //save every backed disc to its file
+        for (d, _) in self.backing.iter() {
+            self.flush_disc(*d)?;
+        }
+        Ok(())
+    }
+
+    fn flush_disc(&self, d: u8) -> io::Result<()> {
+        match self.backing.iter().find(|(n, _)| *n == d) {
+            Some((_, path)) => fs::write(path, &self.mem[d as usize][..]),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn persist(&mut self, d: u8, i: u8, v: u8) -> Result<(), Fault> { //after a write: flush if the program asked to
+        if i == FLUSH_INDEX && v != 0 && self.backing.iter().any(|(n, _)| *n == d) {
+            self.mem[d as usize][i as usize] = 0;
+            self.flush_disc(d).map_err(|err| Fault::Io(err.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn tick_flush(&mut self) -> Result<(), Fault> { //after an instruction: flush if it's time
+        match self.flush_every {
+            Some(n) if self.steps.is_multiple_of(n) => self.flush_discs().map_err(|err| Fault::Io(err.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::{error, fmt, io};
+use std::io::{Read, Write};
+use crate::{ConfigError, Machine, MachineConfig, Permissions, TrapFrame, STACK_DISC};
+
+//snapshot layout, all integers little-endian:
+//magic, version, disc count, register count, every disc, every register,
+//prgcount, dsccount, isactive, jmp, flags (since version 2),
+//stack pointer and stack disc (since version 3),
+//trap vector and trap frame as (set, disc, pc) (since version 4),
+//exit status (since version 5), disc permissions as read 1 | write 2 | execute 4 (since version 6),
+//@0 as saved by the trap frame (since version 7),
+//the trap frame's resume address as (set, disc, pc) and whether the pc has run off the end of its disc (since version 8),
+//steps (u64), cycles (u64)
+pub const MAGIC: &[u8; 4] = b"RSNP";
+pub const VERSION: u8 = 8;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    BadMagic, //not a snapshot at all
+    Version(u8), //written by a newer machine
+    Geometry { discs: u8, registers: u8 }, //written by a machine of a different shape
+    Config(ConfigError), //its stack is somewhere this machine can't have it
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "{}", err),
+            SnapshotError::BadMagic => write!(f, "Not a machine snapshot."),
+            SnapshotError::Version(v) => write!(f, "Snapshot version {} is not supported (expected at most {}).", v, VERSION),
+            SnapshotError::Geometry { discs, registers } => write!(f, "Snapshot is of a machine with {} discs and {} registers, which this one doesn't have.", discs, registers),
+            SnapshotError::Config(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for SnapshotError {}
+
+impl From<ConfigError> for SnapshotError {
+    fn from(err: ConfigError) -> SnapshotError {
+        SnapshotError::Config(err)
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> SnapshotError {
+        SnapshotError::Io(err)
+    }
+}
+
+fn trap<R: Read>(input: &mut R, discs: usize) -> io::Result<Option<(u8, u8)>> {
+    let mut t = [0; 3];
+    input.read_exact(&mut t)?;
+    if t[0] == 0 {
+        Ok(None)
+    } else if t[1] as usize >= discs {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "trap disc out of range"))
+    } else {
+        Ok(Some((t[1], t[2])))
+    }
+}
+
+fn byte<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut b = [0];
+    input.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn long<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut b = [0; 8];
+    input.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+
+impl Machine {
+    pub fn save_state<W: Write>(&self, out: &mut W) -> io::Result<()> { //everything but devices, tracers and debugging aids
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION, self.mem.len() as u8, self.registers.len() as u8])?;
+        for disc in self.mem.iter() {
+            out.write_all(disc)?;
+        }
+        out.write_all(&self.registers)?;
+        out.write_all(&[self.prgcount, self.dsccount, self.isactive as u8, self.jmp as u8, self.flags])?;
+        out.write_all(&[self.sp, self.stack_disc])?;
+        for trap in [self.trap_vector, self.trap_frame.map(|f| (f.disc, f.pc))] {
+            let (d, i) = trap.unwrap_or((0, 0));
+            out.write_all(&[trap.is_some() as u8, d, i])?;
+        }
+        out.write_all(&[self.status])?;
+        for p in self.permissions.iter() {
+            out.write_all(&[p.read as u8 | (p.write as u8) << 1 | (p.execute as u8) << 2])?;
+        }
+        out.write_all(&[self.trap_frame.map_or(0, |f| f.saved)])?;
+        let resume = self.trap_frame.and_then(|f| f.resume);
+        let (d, i) = resume.unwrap_or((0, 0));
+        out.write_all(&[resume.is_some() as u8, d, i, self.past_end as u8])?;
+        out.write_all(&self.steps.to_le_bytes())?;
+        out.write_all(&self.cycles.to_le_bytes())
+    }
+
+    pub fn restore_state<R: Read>(&mut self, input: &mut R) -> Result<(), SnapshotError> { //on failure the machine is left untouched
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = byte(input)?;
+        if version > VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let (discs, registers) = (byte(input)?, byte(input)?);
+        if discs as usize != self.mem.len() || registers as usize != self.registers.len() {
+            return Err(SnapshotError::Geometry { discs, registers });
+        }
+        let discs = discs as usize;
+        let mut mem = vec![[0; 256]; discs];
+        for disc in mem.iter_mut() {
+            input.read_exact(disc)?;
+        }
+        let mut regs = vec![0; registers as usize];
+        input.read_exact(&mut regs)?;
+        let mut state = [0; 4];
+        input.read_exact(&mut state)?;
+        if state[1] as usize >= discs {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "current disc out of range").into());
+        }
+        let flags = if version >= 2 {byte(input)?} else {0};
+        let (sp, stack_disc) = if version >= 3 {(byte(input)?, byte(input)?)} else {(0, STACK_DISC)};
+        let config = MachineConfig { stack_disc, ..self.config.clone() };
+        config.validate()?;
+        let (trap_vector, trap_frame) = if version >= 4 {(trap(input, discs)?, trap(input, discs)?)} else {(None, None)};
+        let status = if version >= 5 {byte(input)?} else {0};
+        let mut permissions = vec![Permissions::ALL; discs];
+        if version >= 6 {
+            for p in permissions.iter_mut() {
+                let b = byte(input)?;
+                *p = Permissions { read: b&1 != 0, write: b&2 != 0, execute: b&4 != 0 };
+            }
+        }
+        let saved = if version >= 7 {byte(input)?} else {0};
+        let resume = if version >= 8 {trap(input, discs)?} else {trap_frame.and_then(|(d, i)| self.locate(d, i, 3).ok())}; //it used to be worked out by RETT
+        let past_end = if version >= 8 {byte(input)? != 0} else {false};
+        let trap_frame = trap_frame.map(|(disc, pc)| TrapFrame { disc, pc, saved, resume });
+        let (steps, cycles) = (long(input)?, long(input)?);
+
+        self.mem = mem;
+        self.registers = regs;
+        self.prgcount = state[0];
+        self.dsccount = state[1];
+        self.isactive = state[2] != 0;
+        self.past_end = past_end;
+        self.jmp = state[3] != 0;
+        self.flags = flags;
+        self.sp = sp;
+        self.stack_disc = stack_disc;
+        self.config = config;
+        self.trap_vector = trap_vector;
+        self.trap_frame = trap_frame;
+        self.status = status;
+        self.permissions = permissions;
+        self.steps = steps;
+        self.cycles = cycles;
+        self.fetched.clear(); //so does what has been run as code
+        self.code_writes.clear();
+        self.clear_journal(); //it describes a different past now
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::Write;
+use crate::{Fault, Instruction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterWrite {
+    pub register: u8,
+    pub old: u8,
+    pub new: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub disc: u8,
+    pub index: u8,
+    pub old: u8,
+    pub new: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEvent { //one executed instruction and what it changed
+    pub step: u64, //counting from 1
+    pub disc: u8,
+    pub pc: u8,
+    pub ins: [u8; 3],
+    pub instruction: Option<Instruction>, //None if the opcode is undefined
+    pub registers: Vec<RegisterWrite>,
+    pub memory: Vec<MemoryWrite>,
+}
+
+impl TraceEvent {
+    pub fn mnemonic(&self) -> String {
+        match self.instruction {
+            Some(x) => x.to_string(),
+            None => String::from("???"),
+        }
+    }
+}
+
+pub trait Tracer { //called by the machine after every instruction it completes
+    fn trace(&mut self, event: &TraceEvent) -> Result<(), Fault>;
+}
+
+fn io(err: std::io::Error) -> Fault {
+    Fault::Io(err.to_string())
+}
+
+pub struct JsonLines<W: Write> { //one JSON object per line
+    out: W,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(out: W) -> JsonLines<W> {
+        JsonLines { out }
+    }
+}
+
+impl<W: Write> Tracer for JsonLines<W> {
+    fn trace(&mut self, e: &TraceEvent) -> Result<(), Fault> {
+        let registers: Vec<String> = e.registers.iter()
+            .map(|w| format!("{{\"register\":{},\"old\":{},\"new\":{}}}", w.register, w.old, w.new))
+            .collect();
+        let memory: Vec<String> = e.memory.iter()
+            .map(|w| format!("{{\"disc\":{},\"index\":{},\"old\":{},\"new\":{}}}", w.disc, w.index, w.old, w.new))
+            .collect();
+        writeln!(self.out, "{{\"step\":{},\"disc\":{},\"pc\":{},\"bytes\":[{},{},{}],\"ins\":{:?},\"registers\":[{}],\"memory\":[{}]}}",
+            e.step, e.disc, e.pc, e.ins[0], e.ins[1], e.ins[2], e.mnemonic(), registers.join(","), memory.join(",")).map_err(io)
+    }
+}
+
+pub struct Csv<W: Write> { //one row per instruction; writes are `@r=old>new` and `:d i=old>new`, separated by spaces
+    out: W,
+    header: bool,
+}
+
+impl<W: Write> Csv<W> {
+    pub fn new(out: W) -> Csv<W> {
+        Csv { out, header: false }
+    }
+}
+
+impl<W: Write> Tracer for Csv<W> {
+    fn trace(&mut self, e: &TraceEvent) -> Result<(), Fault> {
+        if !self.header {
+            writeln!(self.out, "step,disc,pc,bytes,ins,registers,memory").map_err(io)?;
+            self.header = true;
+        }
+        let registers: Vec<String> = e.registers.iter()
+            .map(|w| format!("@{}={}>{}", w.register, w.old, w.new))
+            .collect();
+        let memory: Vec<String> = e.memory.iter()
+            .map(|w| format!(":{} {}={}>{}", w.disc, w.index, w.old, w.new))
+            .collect();
+        writeln!(self.out, "{},{},{},{:08b} {:08b} {:08b},{},{},{}",
+            e.step, e.disc, e.pc, e.ins[0], e.ins[1], e.ins[2], e.mnemonic(), registers.join(" "), memory.join(" ")).map_err(io)
+    }
+}
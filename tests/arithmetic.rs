@@ -0,0 +1,76 @@
+mod common;
+
+use common::{program, split_rex};
+use risc_vm::{Console, Fault, Machine, Stop, CONSOLE_DISC};
+use risc_vm::Instruction::*;
+
+fn output(program: &[[u8; 256]]) -> (String, Machine) { //run to the end, returning what was printed
+    let mut vm = Machine::new();
+    vm.load_program(program).unwrap();
+    let (console, capture) = Console::capture();
+    vm.attach(CONSOLE_DISC, Box::new(console)).unwrap();
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    (capture.text(), vm)
+}
+
+#[test]
+fn mult_example() {
+    assert_eq!(output(&split_rex(include_bytes!("../mult.rex"))).0, "8");
+}
+
+#[test]
+fn factorial_example() {
+    assert_eq!(output(&split_rex(include_bytes!("../fact.rex"))).0, "H");
+}
+
+#[test]
+fn mul_matches_mult_example() {
+    let (text, vm) = output(&[program(&[
+        SetRV(1, 2),
+        MulRV(1, 4),
+        AddRV(0, 48),
+        SaveDIR(0, 0, CONSOLE_DISC),
+        SaveDIV(1, 1, CONSOLE_DISC),
+        End(0),
+    ])]);
+    assert_eq!(text, "8");
+    assert!(vm.cycles() < output(&split_rex(include_bytes!("../mult.rex"))).1.cycles());
+}
+
+#[test]
+fn mul_matches_factorial_example() {
+    let (text, _) = output(&[program(&[
+        SetRV(7, 4),
+        SetRV(6, 3),
+        MulRR(7, 6),
+        SetRR(7, 0),
+        SubRV(6, 1),
+        SetRR(6, 0),
+        JnzDRI(6, 6, 0),
+        AddRV(7, 48),
+        SaveDIR(0, 0, CONSOLE_DISC),
+        SaveDIV(1, 1, CONSOLE_DISC),
+        End(0),
+    ])]);
+    assert_eq!(text, "H");
+}
+
+#[test]
+fn mul_high_byte() {
+    let (_, vm) = output(&[program(&[SetRV(2, 200), MulRV(2, 3), End(0)])]);
+    assert_eq!(vm.registers()[..2], [600u16 as u8, 2]);
+}
+
+#[test]
+fn div_and_mod() {
+    let (_, vm) = output(&[program(&[SetRV(2, 47), ModRV(2, 10), SetRR(3, 0), DivRV(2, 10), End(0)])]);
+    assert_eq!((vm.registers()[0], vm.registers()[3]), (4, 7));
+}
+
+#[test]
+fn divide_by_zero() {
+    let mut vm = Machine::new();
+    vm.load_program(&[program(&[SetRV(2, 1), DivVR(5, 3), End(0)])]).unwrap();
+    let err = vm.run().unwrap_err();
+    assert_eq!((err.fault, err.pc), (Fault::DivideByZero, 3));
+}
@@ -0,0 +1,110 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+fn rvm(args: &[&str]) -> (i32, String) { //exit code and output of the command line
+    let out = Command::new(env!("CARGO_BIN_EXE_risc_vm")).args(args).output().unwrap();
+    (out.status.code().unwrap(), String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("risc_vm_cli_{}_{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn run(name: &str, rasm: &str) -> (i32, String) { //assemble, then run
+    run_with(name, rasm, &[])
+}
+
+fn run_with(name: &str, rasm: &str, options: &[&str]) -> (i32, String) { //with the same options for both
+    let src = file(&format!("{}.rasm", name), rasm);
+    let rex = src.with_extension("rex");
+    let compiled = rvm(&[&["compile", src.to_str().unwrap(), rex.to_str().unwrap()], options].concat());
+    assert_eq!(compiled.0, 0, "{}", compiled.1);
+    let result = rvm(&[&["run", rex.to_str().unwrap()], options].concat());
+    fs::remove_file(src).ok();
+    fs::remove_file(rex).ok();
+    result
+}
+
+#[test]
+fn status_is_the_exit_code() {
+    assert_eq!(run("pass", "END\n").0, 0);
+    assert_eq!(run("fail", "END 3\n").0, 3);
+    assert_eq!(run("highest", "END 199\n").0, 199);
+}
+
+#[test]
+fn machine_failures_are_above_statuses() {
+    assert_eq!(run("fault", "DIV @1 0\n").0, 203);
+    let red = file("undefined.red", "00000000 11010010 00000000\n"); //END 210, which the assembler won't write
+    assert_eq!(rvm(&["run", red.to_str().unwrap()]).0, 213);
+    fs::remove_file(red).ok();
+    assert_eq!(rvm(&["run"]).0, 201);
+}
+
+#[test]
+fn status_too_big_to_assemble() {
+    for status in ["200", "255"] {
+        let src = file(&format!("end{}.rasm", status), &format!("END {}\n", status));
+        let (code, out) = rvm(&["compile", src.to_str().unwrap(), src.with_extension("rex").to_str().unwrap()]);
+        assert_eq!(code, 207, "{}", out);
+        fs::remove_file(src).ok();
+    }
+}
+
+#[test]
+fn small_machine() {
+    let program = "SET @1 65\nPUSH @1\nPOP @2\nSAVE :1 0 @2\nSAVE :1 1 1\nEND 5\n";
+    let (code, out) = run_with("small", program, &["--discs", "3", "--map", "0", "--console-disc", "1", "--stack-disc", "2"]);
+    assert_eq!((code, out.starts_with('A')), (5, true), "{}", out);
+    assert_eq!(run_with("tiny", "END 6\n", &["--discs", "2", "--map", "0", "--console-disc", "none", "--stack-disc", "1"]).0, 6);
+    let (code, out) = run_with("too_small", "END\n", &["--discs", "3", "--map", "0"]);
+    assert_eq!(code, 201, "{}", out);
+}
+
+#[test]
+fn bad_maps() {
+    for map in ["0,0", "0,4", "6"] {
+        let (code, out) = run_with(&format!("map{}", map.replace(',', "_")), "END\n", &["--map", map]);
+        assert_eq!(code, 201, "{}: {}", map, out);
+    }
+}
+
+#[test]
+fn resume_keeps_the_stack() {
+    let src = file("stack.rasm", "SET @1 65\nPUSH @1\nPOP @2\nSAVE :4 0 @2\nSAVE :4 1 1\nEND\n");
+    let (rex, snap) = (src.with_extension("rex"), src.with_extension("rsnap"));
+    assert_eq!(rvm(&["compile", src.to_str().unwrap(), rex.to_str().unwrap()]).0, 0);
+    assert_eq!(rvm(&["run", rex.to_str().unwrap(), "--max-steps", "2", "--save-state", snap.to_str().unwrap()]).0, 208);
+    for options in [&[][..], &["--stack-disc", "6"]] {
+        let (code, out) = rvm(&[&["resume", snap.to_str().unwrap()], options].concat());
+        assert_eq!((code, out.starts_with('A')), (0, true), "{}", out);
+    }
+    for path in [src, rex, snap] {
+        fs::remove_file(path).ok();
+    }
+}
+
+#[test]
+fn step_budget() {
+    let (code, out) = run_with("forever", "JMP 0 :0\n", &["--max-steps", "50"]);
+    assert_eq!(code, 208, "{}", out);
+    assert_eq!(run_with("cycles", "JMP 0 :0\n", &["--max-cycles", "50"]).0, 208);
+}
+
+#[test]
+fn undefined_opcode() {
+    let red = file("undefined_op.red", "11110111 00000000 00000000\n00000000 00000000 00000000\n");
+    assert_eq!(rvm(&["run", red.to_str().unwrap()]).0, 210);
+    assert_eq!(rvm(&["run", red.to_str().unwrap(), "--strict"]).0, 211);
+    fs::remove_file(red).ok();
+}
+
+#[test]
+fn protect_code() {
+    let overwrite = "SAVE :0 2 7\nEND\n";
+    assert_eq!(run_with("unprotected", overwrite, &[]).0, 0);
+    let (code, out) = run_with("protected", overwrite, &["--protect-code"]);
+    assert_eq!(code, 203, "{}", out);
+    assert!(out.contains("write"), "{}", out);
+}
@@ -0,0 +1,25 @@
+#![allow(dead_code)] //each test file only uses some of these
+
+use risc_vm::{encode, Instruction};
+
+pub fn program(instructions: &[Instruction]) -> [u8; 256] { //one disc, with the instructions from index 0
+    let mut disc = [0; 256];
+    for (i, ins) in instructions.iter().enumerate() {
+        put(&mut disc, i*3, *ins);
+    }
+    disc
+}
+
+pub fn put(disc: &mut [u8; 256], at: usize, ins: Instruction) {
+    disc[at..at+3].copy_from_slice(&encode(ins));
+}
+
+pub fn split_rex(rex: &[u8]) -> Vec<[u8; 256]> { //split a .rex image into discs
+    rex.split(|b| *b == 0b11111111)
+        .map(|d| {
+            let mut disc = [0; 256];
+            disc[..d.len()].copy_from_slice(d);
+            disc
+        })
+        .collect()
+}
@@ -0,0 +1,49 @@
+use risc_vm::{ConfigError, LoadError, Machine, MachineConfig, StandardDevice};
+
+fn mapped(map: &[u8]) -> MachineConfig {
+    MachineConfig { program_discs: map.to_vec(), ..MachineConfig::default() }
+}
+
+#[test]
+fn program_map() {
+    assert_eq!(mapped(&[0, 1, 2, 3, 7]).validate(), Ok(()));
+    assert_eq!(mapped(&[0, 0]).validate(), Err(ConfigError::MappedTwice(0)));
+    assert_eq!(mapped(&[0, 4]).validate(), Err(ConfigError::MappedOver(4))); //the console
+    assert_eq!(mapped(&[6]).validate(), Err(ConfigError::MappedOver(6))); //the stack
+    let mut config = mapped(&[5]);
+    assert_eq!(config.validate(), Ok(())); //plain memory without input
+    config.devices.push((5, StandardDevice::Input(risc_vm::ReadMode::NonBlocking)));
+    assert_eq!(config.validate(), Err(ConfigError::MappedOver(5)));
+    assert!(Machine::with_config(mapped(&[1, 1])).is_err());
+}
+
+#[test]
+fn load_errors() {
+    let mut vm = Machine::new();
+    assert_eq!(vm.load_mapped(&[[0; 256]; 2], &[0]), Err(LoadError::DoesNotFit { sections: 2, discs: 1 }));
+    assert_eq!(vm.load_mapped(&[[1; 256]; 2], &[0, 9]), Err(LoadError::NoDisc(9)));
+    assert_eq!(vm.disc(0).unwrap()[0], 0); //nothing was loaded
+    assert_eq!(vm.load_disc(8, [1; 256]), Err(LoadError::NoDisc(8)));
+    assert_eq!(vm.load_program(&[[0; 256]; 7]), Err(LoadError::DoesNotFit { sections: 7, discs: 6 }));
+}
+
+#[test]
+fn default_map_is_every_free_disc() {
+    assert_eq!(MachineConfig::default().program_discs, vec![0, 1, 2, 3, 5, 7]);
+    let mut config = MachineConfig { stack_disc: 2, ..MachineConfig::default() };
+    config.devices.push((5, StandardDevice::Input(risc_vm::ReadMode::NonBlocking)));
+    assert_eq!(config.free_discs(), vec![0, 1, 3, 6, 7]);
+    let mut vm = Machine::new();
+    vm.load_program(&[[1; 256]; 6]).unwrap();
+    assert_eq!((vm.disc(5).unwrap()[0], vm.disc(7).unwrap()[0], vm.disc(6).unwrap()[0]), (1, 1, 0));
+}
+
+#[test]
+fn moving_the_stack_keeps_the_config() {
+    let mut vm = Machine::with_config(mapped(&[0, 1, 2, 3])).unwrap();
+    assert_eq!(vm.set_stack_disc(2), Err(ConfigError::MappedOver(2)));
+    assert_eq!(vm.set_stack_disc(9), Err(ConfigError::NoDisc(9)));
+    assert_eq!(vm.stack_disc(), 6);
+    vm.set_stack_disc(7).unwrap();
+    assert_eq!((vm.stack_disc(), vm.config().stack_disc), (7, 7));
+}
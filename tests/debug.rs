@@ -0,0 +1,62 @@
+mod common;
+
+use risc_vm::{Access, Hit, Machine, MemoryWatch, Stop};
+use risc_vm::Instruction::*;
+
+fn machine() -> Machine { //writes 5 to :2 10, reads it back, then sets @1 to the same value and a new one
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(&[
+        SetRV(1, 5),
+        SaveDIR(10, 1, 2),
+        LoadDIR(10, 3, 2),
+        SetRV(1, 5),
+        SetRV(1, 6),
+        End(0),
+    ])]).unwrap();
+    vm
+}
+
+fn watch(from: u8, to: u8, read: bool, write: bool) -> MemoryWatch {
+    MemoryWatch { disc: 2, from, to, read, write }
+}
+
+#[test]
+fn breakpoint_stops_then_resumes() {
+    let mut vm = machine();
+    vm.add_breakpoint(0, 6);
+    assert_eq!(vm.run().unwrap(), Stop::Breakpoint { disc: 0, pc: 6 });
+    assert_eq!((vm.prgcount(), vm.steps(), vm.registers()[3]), (6, 2, 0)); //stopped before it
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0)); //and carries on past it
+    assert_eq!(vm.registers()[3], 5);
+    assert!(vm.remove_breakpoint(0, 6));
+    assert!(!vm.remove_breakpoint(0, 6));
+}
+
+#[test]
+fn memory_watches() {
+    let written = Stop::Watchpoint(Hit::Memory { disc: 2, index: 10, access: Access::Write, value: 5 });
+    let read = Stop::Watchpoint(Hit::Memory { disc: 2, index: 10, access: Access::Read, value: 5 });
+    for (w, stops) in [
+        (watch(8, 12, false, true), vec![(written, 6)]),
+        (watch(10, 10, true, false), vec![(read, 9)]),
+        (watch(0, 255, true, true), vec![(written, 6), (read, 9)]),
+        (watch(0, 9, true, true), vec![]),
+    ] {
+        let mut vm = machine();
+        vm.watch_memory(w);
+        for (stop, pc) in stops { //stopped after the instruction that did it
+            assert_eq!((vm.run().unwrap(), vm.prgcount()), (stop, pc), "{:?}", w);
+        }
+        assert_eq!(vm.run().unwrap(), Stop::Halted(0), "{:?}", w);
+    }
+}
+
+#[test]
+fn register_watch_needs_a_change() {
+    let mut vm = machine();
+    vm.watch_register(1);
+    assert_eq!(vm.run().unwrap(), Stop::Watchpoint(Hit::Register { register: 1, old: 0, new: 5 }));
+    assert_eq!(vm.run().unwrap(), Stop::Watchpoint(Hit::Register { register: 1, old: 5, new: 6 })); //not the SET to 5 again
+    assert_eq!(vm.prgcount(), 15);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::put;
+use risc_vm::{encode, Fault, FetchPolicy, Machine, Permissions, Stop};
+use risc_vm::Instruction::*;
+
+const HANDLER: u8 = 60;
+
+//jumps to `at` on disc 0, where SET @1 7 fits at 253 and an END straddles 254 or 255;
+//coming back round to the start afterwards ends with 2, and disc 1 ends with 3
+fn machine(policy: FetchPolicy, at: u8, trapped: bool) -> Machine {
+    let mut disc = [0; 256];
+    put(&mut disc, 0, JnzDRI(1, 9, 0));
+    put(&mut disc, 3, if trapped {TrapDI(HANDLER, 0)} else {SetRV(2, 0)});
+    put(&mut disc, 6, JmpDI(at, 0));
+    put(&mut disc, 9, End(2));
+    put(&mut disc, HANDLER as usize, SetRR(5, 0));
+    put(&mut disc, HANDLER as usize + 3, Rett);
+    match at {
+        253 => put(&mut disc, 253, SetRV(1, 7)),
+        254 => disc[254..].copy_from_slice(&[0, 4]), //END 4, whatever the third byte is
+        _ => disc[255] = 0, //END, with the status from the next disc along
+    }
+    let mut next = [0; 256];
+    put(&mut next, 0, End(3));
+    let mut vm = Machine::new();
+    vm.load_program(&[disc, next]).unwrap();
+    vm.set_fetch_policy(policy);
+    vm
+}
+
+#[test]
+fn wrap() {
+    assert_eq!(machine(FetchPolicy::Wrap, 253, false).run().unwrap(), Stop::Halted(2));
+    assert_eq!(machine(FetchPolicy::Wrap, 254, false).run().unwrap(), Stop::Halted(4));
+    assert_eq!(machine(FetchPolicy::Wrap, 255, false).run().unwrap(), Stop::Halted(encode(JnzDRI(1, 9, 0))[0]));
+}
+
+#[test]
+fn next_disc() {
+    assert_eq!(machine(FetchPolicy::NextDisc, 253, false).run().unwrap(), Stop::Halted(3));
+    assert_eq!(machine(FetchPolicy::NextDisc, 254, false).run().unwrap(), Stop::Halted(4));
+    assert_eq!(machine(FetchPolicy::NextDisc, 255, false).run().unwrap(), Stop::Halted(0));
+}
+
+#[test]
+fn last_instruction_completes_before_the_fault() {
+    let mut vm = machine(FetchPolicy::Fault, 253, false);
+    assert_eq!(vm.run_for(4).unwrap(), None);
+    assert_eq!((vm.registers()[1], vm.prgcount(), vm.steps()), (7, 253, 4));
+    assert!(vm.is_past_end());
+    let err = vm.step().unwrap_err();
+    assert_eq!((err.fault, err.disc, err.pc, vm.steps()), (Fault::FetchPastEnd, 0, 253, 4));
+}
+
+#[test]
+fn straddling_instruction_faults() {
+    for at in [254, 255] {
+        let mut vm = machine(FetchPolicy::Fault, at, false);
+        let err = vm.run().unwrap_err();
+        assert_eq!((err.fault, err.pc, vm.steps()), (Fault::FetchPastEnd, at, 3));
+        assert!(!vm.is_past_end());
+    }
+}
+
+#[test]
+fn trapped_with_nowhere_to_return() {
+    for at in [253, 254, 255] {
+        let mut vm = machine(FetchPolicy::Fault, at, true);
+        let err = vm.run().unwrap_err();
+        assert_eq!(vm.registers()[5], 4, "{}", at); //the handler ran
+        let frame = vm.trap_frame().unwrap();
+        assert_eq!((frame.disc, frame.pc, frame.resume), (0, at, None));
+        assert_eq!((err.fault, err.pc), (Fault::FetchPastEnd, HANDLER + 3)); //RETT has nothing to carry on at
+    }
+}
+
+#[test]
+fn trap_frame_keeps_where_to_resume() {
+    let mut vm = machine(FetchPolicy::NextDisc, 253, true);
+    vm.set_permissions(1, Permissions::DATA).unwrap(); //can't run disc 1, so the fetch there is trapped
+    while vm.trap_frame().is_none() {
+        vm.step().unwrap();
+    }
+    let frame = vm.trap_frame().unwrap();
+    assert_eq!((frame.disc, frame.pc, frame.resume), (1, 0, Some((1, 3))));
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use risc_vm::{Instruction, Machine, FLAG_CARRY, FLAG_NEGATIVE, FLAG_OVERFLOW, FLAG_ZERO};
+use risc_vm::Instruction::*;
+
+fn run(program: &[Instruction]) -> Machine {
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(&[program, &[End(0)]].concat())]).unwrap();
+    vm.run().unwrap();
+    vm
+}
+
+fn add(a: u8, b: u8) -> (u8, u8) { //result and flags
+    let vm = run(&[SetRV(1, a), AddRV(1, b)]);
+    (vm.registers()[0], vm.flags())
+}
+
+fn sub(a: u8, b: u8) -> (u8, u8) {
+    let vm = run(&[SetRV(1, a), SubRV(1, b)]);
+    (vm.registers()[0], vm.flags())
+}
+
+#[test]
+fn add_flags() {
+    assert_eq!(add(1, 2), (3, 0));
+    assert_eq!(add(200, 100), (44, FLAG_CARRY));
+    assert_eq!(add(100, 100), (200, FLAG_OVERFLOW | FLAG_NEGATIVE));
+    assert_eq!(add(128, 128), (0, FLAG_ZERO | FLAG_CARRY | FLAG_OVERFLOW));
+    assert_eq!(add(255, 1), (0, FLAG_ZERO | FLAG_CARRY));
+    assert_eq!(add(255, 255), (254, FLAG_CARRY | FLAG_NEGATIVE));
+}
+
+#[test]
+fn sub_flags() {
+    assert_eq!(sub(10, 3), (7, 0));
+    assert_eq!(sub(10, 10), (0, FLAG_ZERO));
+    assert_eq!(sub(5, 10), (251, FLAG_CARRY | FLAG_NEGATIVE)); //carry is a borrow
+    assert_eq!(sub(128, 1), (127, FLAG_OVERFLOW));
+    assert_eq!(sub(127, 255), (128, FLAG_CARRY | FLAG_OVERFLOW | FLAG_NEGATIVE));
+    let vm = run(&[SetRV(1, 1), SubVR(0, 1)]);
+    assert_eq!((vm.registers()[0], vm.flags()), (255, FLAG_CARRY | FLAG_NEGATIVE));
+}
+
+#[test]
+fn adc_carries_between_bytes() { //0x01ff + 0x0001, a byte at a time
+    let vm = run(&[SetRV(1, 0xff), SetRV(2, 0x01), AddRV(1, 0x01), SetRR(3, 0), AdcRV(2, 0x00), SetRR(4, 0)]);
+    assert_eq!((vm.registers()[4], vm.registers()[3]), (0x02, 0x00));
+    assert_eq!(vm.flags(), 0);
+}
+
+#[test]
+fn sbc_borrows_between_bytes() { //0x0200 - 0x0001, a byte at a time
+    let vm = run(&[SetRV(1, 0x00), SetRV(2, 0x02), SubRV(1, 0x01), SetRR(3, 0), SbcRV(2, 0x00), SetRR(4, 0)]);
+    assert_eq!((vm.registers()[4], vm.registers()[3]), (0x01, 0xff));
+    assert_eq!(vm.flags(), 0);
+}
+
+#[test]
+fn sbc_borrow_in() {
+    let borrowed = |a, b| { //a - b - 1, with the carry set by a subtract that borrowed
+        let vm = run(&[SetRV(2, 0), SubRV(2, 1), SetRV(1, a), SbcRV(1, b)]);
+        (vm.registers()[0], vm.flags())
+    };
+    assert_eq!(borrowed(5, 2), (2, 0));
+    assert_eq!(borrowed(1, 0), (0, FLAG_ZERO));
+    assert_eq!(borrowed(0, 0), (255, FLAG_CARRY | FLAG_NEGATIVE)); //the borrow in alone borrows out
+    assert_eq!(borrowed(5, 5), (255, FLAG_CARRY | FLAG_NEGATIVE));
+    let vm = run(&[SetRV(1, 5), SbcRV(1, 2)]); //no borrow in
+    assert_eq!(vm.registers()[0], 3);
+}
+
+#[test]
+fn logic_clears_carry_and_overflow() {
+    let vm = run(&[SetRV(1, 128), AddRV(1, 128), SetRV(2, 0xf0), AndRV(2, 0x80)]);
+    assert_eq!((vm.registers()[0], vm.flags()), (0x80, FLAG_NEGATIVE));
+}
+
+#[test]
+fn compare_sets_flags_like_sub() {
+    let vm = run(&[SetRV(1, 3), LstRV(1, 4)]);
+    assert_eq!((vm.registers()[0], vm.flags()), (1, FLAG_CARRY | FLAG_NEGATIVE));
+    let vm = run(&[SetRV(1, 4), EqlRV(1, 4)]);
+    assert_eq!((vm.registers()[0], vm.flags()), (1, FLAG_ZERO));
+}
+
+fn shift(op: fn(u8, u8) -> Instruction, n: u8) -> (u8, u8) { //0b10000001 by n, with n in a register
+    let vm = run(&[SetRV(1, 0b10000001), SetRV(2, n), op(1, 2)]);
+    (vm.registers()[0], vm.flags())
+}
+
+#[test]
+fn shifts() {
+    let cases = [(0, 0b10000001, FLAG_NEGATIVE), (1, 0b00000010, FLAG_CARRY), (7, 0b10000000, FLAG_NEGATIVE), (8, 0, FLAG_ZERO | FLAG_CARRY), (9, 0, FLAG_ZERO), (255, 0, FLAG_ZERO)];
+    for &(n, x, flags) in cases.iter() {
+        assert_eq!(shift(ShlRR, n), (x, flags), "SHL {}", n);
+        let vm = run(&[SetRV(1, 0b10000001), ShlRV(1, n)]);
+        assert_eq!((vm.registers()[0], vm.flags()), (x, flags), "SHL {}", n);
+    }
+    let cases = [(0, 0b10000001, FLAG_NEGATIVE), (1, 0b01000000, FLAG_CARRY), (7, 0b00000001, 0), (8, 0, FLAG_ZERO | FLAG_CARRY), (9, 0, FLAG_ZERO), (255, 0, FLAG_ZERO)];
+    for &(n, x, flags) in cases.iter() {
+        assert_eq!(shift(ShrRR, n), (x, flags), "SHR {}", n);
+        let vm = run(&[SetRV(1, 0b10000001), ShrRV(1, n)]);
+        assert_eq!((vm.registers()[0], vm.flags()), (x, flags), "SHR {}", n);
+    }
+}
+
+#[test]
+fn rotates() { //by a multiple of 8 nothing goes round, so there is no carry
+    let cases = [(0, 0b10000001, FLAG_NEGATIVE), (1, 0b00000011, FLAG_CARRY), (7, 0b11000000, FLAG_NEGATIVE), (8, 0b10000001, FLAG_NEGATIVE), (9, 0b00000011, FLAG_CARRY), (255, 0b11000000, FLAG_NEGATIVE)];
+    for &(n, x, flags) in cases.iter() {
+        assert_eq!(shift(RolRR, n), (x, flags), "ROL {}", n);
+        let vm = run(&[SetRV(1, 0b10000001), RolRV(1, n)]);
+        assert_eq!((vm.registers()[0], vm.flags()), (x, flags), "ROL {}", n);
+    }
+    let cases = [(0, 0b10000001, FLAG_NEGATIVE), (1, 0b11000000, FLAG_CARRY | FLAG_NEGATIVE), (7, 0b00000011, 0), (8, 0b10000001, FLAG_NEGATIVE), (9, 0b11000000, FLAG_CARRY | FLAG_NEGATIVE), (255, 0b00000011, 0)];
+    for &(n, x, flags) in cases.iter() {
+        assert_eq!(shift(RorRR, n), (x, flags), "ROR {}", n);
+        let vm = run(&[SetRV(1, 0b10000001), RorRV(1, n)]);
+        assert_eq!((vm.registers()[0], vm.flags()), (x, flags), "ROR {}", n);
+    }
+}
+
+#[test]
+fn single_bits() { //bit numbers wrap at 8
+    assert_eq!(shift(BtstRR, 0), (1, 0));
+    assert_eq!(shift(BtstRR, 1), (0, FLAG_ZERO));
+    assert_eq!(shift(BtstRR, 15), (1, 0));
+    assert_eq!(shift(BsetRR, 1), (0b10000011, FLAG_NEGATIVE));
+    assert_eq!(shift(BsetRR, 9), (0b10000011, FLAG_NEGATIVE));
+    assert_eq!(shift(BclrRR, 7), (0b00000001, 0));
+    assert_eq!(shift(BclrRR, 0), (0b10000000, FLAG_NEGATIVE));
+    for (op, n, x) in [(BtstRV as fn(u8, u8) -> Instruction, 7, 1), (BsetRV, 10, 0b10000101), (BclrRV, 8, 0b10000000)] {
+        let vm = run(&[SetRV(1, 0b10000001), op(1, n)]);
+        assert_eq!(vm.registers()[0], x, "{}", op(1, n));
+    }
+}
+
+#[test]
+fn popc() {
+    for (a, count, flags) in [(0b10000001, 2, 0), (0, 0, FLAG_ZERO), (255, 8, 0)] {
+        let vm = run(&[SetRV(1, a), AddRV(1, 0), Popc(1)]); //the ADD leaves flags for POPC to clear
+        assert_eq!((vm.registers()[0], vm.flags()), (count, flags), "POPC {}", a);
+    }
+}
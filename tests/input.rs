@@ -0,0 +1,61 @@
+mod common;
+
+use common::{program, split_rex};
+use risc_vm::{Console, Device, Input, Machine, ReadMode, Stop, CONSOLE_DISC, INPUT_DISC};
+use risc_vm::Instruction::*;
+
+fn echo(input: &[u8], mode: ReadMode) -> String { //run echo.rex over scripted input
+    let mut vm = Machine::new();
+    vm.load_program(&split_rex(include_bytes!("../echo.rex"))).unwrap();
+    let (console, capture) = Console::capture();
+    vm.attach(CONSOLE_DISC, Box::new(console)).unwrap();
+    vm.attach(INPUT_DISC, Box::new(Input::from_bytes(input.to_vec(), mode))).unwrap();
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    capture.text()
+}
+
+#[test]
+fn echo_blocking() {
+    assert_eq!(echo(b"hello", ReadMode::Blocking), "hello");
+}
+
+#[test]
+fn echo_non_blocking() {
+    assert_eq!(echo(b"hello", ReadMode::NonBlocking), "hello");
+}
+
+#[test]
+fn echo_nothing() {
+    assert_eq!(echo(b"", ReadMode::Blocking), "");
+}
+
+#[test]
+fn byte_available_and_end_of_input() {
+    let mut input = Input::from_bytes(vec![7, 8], ReadMode::Blocking);
+    let mut disc = [0; 256];
+    input.on_read(&mut disc, 1).unwrap();
+    assert_eq!(disc[..3], [7, 1, 0]);
+    input.on_read(&mut disc, 1).unwrap(); //not taken yet, so still the same byte
+    assert_eq!(disc[..3], [7, 1, 0]);
+    disc[1] = 0;
+    input.on_read(&mut disc, 0).unwrap();
+    assert_eq!(disc[..3], [8, 1, 0]);
+    disc[1] = 0;
+    input.on_read(&mut disc, 1).unwrap();
+    assert_eq!(disc[1..3], [0, 1]);
+}
+
+#[test]
+fn non_blocking_fills_on_tick() {
+    let mut input = Input::from_bytes(vec![9], ReadMode::NonBlocking);
+    let mut disc = [0; 256];
+    input.tick(&mut disc).unwrap();
+    assert_eq!(disc[..3], [9, 1, 0]);
+}
+
+#[test]
+fn input_disc_is_memory_by_default() {
+    let mut vm = risc_vm::boot(&[program(&[SaveDIV(1, 42, INPUT_DISC), LoadDIR(1, 3, INPUT_DISC), End(0)])]).unwrap();
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert_eq!(vm.registers()[3], 42);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::{env, fs, process::Command};
+use risc_vm::{decode, encode, Machine};
+use risc_vm::Instruction::*;
+
+fn assemble(rasm: &str, name: &str) -> Result<Vec<u8>, String> { //assemble with the real assembler, or say why it wouldn't
+    let dir = env::temp_dir();
+    let src = dir.join(format!("risc_vm_{}_{}.rasm", name, std::process::id()));
+    let out = src.with_extension("rex");
+    fs::write(&src, rasm).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_risc_vm"))
+        .args(["compile", src.to_str().unwrap(), out.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let rex = fs::read(&out);
+    fs::remove_file(src).ok();
+    fs::remove_file(out).ok();
+    if status.status.success() {
+        Ok(rex.unwrap())
+    } else {
+        Err(String::from_utf8_lossy(&status.stdout).into_owned())
+    }
+}
+
+fn compile(rasm: &str, name: &str) -> Vec<u8> {
+    assemble(rasm, name).unwrap()
+}
+
+#[test]
+fn disassembly_assembles_back() { //every opcode, through Display and the assembler, comes back as the same bytes
+    let instructions: Vec<_> = (0..=254u8).filter_map(|op| decode([op, 1, 2]).ok()).collect();
+    let mut rasm = vec![];
+    let mut expected = vec![];
+    for disc in instructions.chunks(80) {
+        rasm.push(disc.iter().map(|ins| format!("{}\n", ins)).collect::<String>());
+        for ins in disc {
+            expected.extend_from_slice(&encode(*ins));
+        }
+        expected.push(0b11111111);
+    }
+    assert_eq!(compile(&rasm.join("---\n"), "roundtrip"), expected);
+}
+
+#[test]
+fn jump_to_disc_in_register() {
+    assert_eq!(format!("{}", JmpRI(5, 3)), "JMP 5 :@3");
+    assert_eq!(format!("{}", JmpRR(5, 3)), "JMP @5 :@3");
+    assert_eq!(compile("JMP 5 :@3\nJMP @5 :@3\n", "jump"), [0b10000101, 5, 3, 0b10000110, 5, 3, 0b11111111]);
+}
+
+#[test]
+fn load_with_index_in_register() {
+    let mut vm = Machine::new();
+    vm.poke(2, 7, 42).unwrap();
+    vm.poke(2, 3, 99).unwrap();
+    vm.set_register(1, 7).unwrap();
+    vm.set_register(3, 2).unwrap();
+    vm.load_program(&[common::program(&[LoadDRR(1, 4, 2), LoadRRR(1, 5, 3), End(0)])]).unwrap();
+    vm.run().unwrap();
+    assert_eq!((vm.registers()[4], vm.registers()[5]), (42, 42));
+    assert_eq!(format!("{}", LoadDRR(1, 4, 2)), "LOAD :2 @1 @4");
+}
+
+#[test]
+fn cycle_costs() {
+    let costs: Vec<u64> = [AddRV(0, 1), SaveDIV(0, 1, 2), LoadRRR(0, 1, 2), Push(0), CallDI(0, 1), Ret, MulRR(0, 1), DivVR(0, 1), ModRV(0, 1), End(0)]
+        .iter()
+        .map(|ins| ins.cycles())
+        .collect();
+    assert_eq!(costs, [1, 2, 2, 2, 3, 3, 4, 8, 8, 1]);
+}
+
+#[test]
+fn jump_to_register_or_value() {
+    assert_eq!(compile("JMP 0 :0\nJMP @1 :0\n", "jmp"), [0b10000011, 0, 0, 0b10000100, 1, 0, 0b11111111]);
+    assert!(assemble("JMP 0 :8\n", "jmp_disc").is_err()); //the disc is checked, not the index
+    assert!(compile("JMP 200 :7\n", "jmp_index").starts_with(&[0b10000011, 200, 7]));
+    assert!(assemble("JMP @8 :0\n", "jmp_register").is_err());
+    assert!(assemble("JMP @1 :8\n", "jmp_register_disc").is_err());
+}
+
+#[test]
+fn echo_example_is_up_to_date() {
+    assert_eq!(compile(include_str!("../echo.rasm"), "echo"), include_bytes!("../echo.rex"));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "SAVE can only name discs and registers 0 to 7")]
+fn embedded_operand_must_fit() {
+    encode(SaveDIV(1, 2, 9));
+}
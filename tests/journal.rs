@@ -0,0 +1,112 @@
+mod common;
+
+use risc_vm::{Instruction, Machine, Stop};
+use risc_vm::Instruction::*;
+
+fn machine(program: &[Instruction]) -> Machine { //journaling, with the program loaded
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(program)]).unwrap();
+    vm.set_journal_limit(100);
+    vm
+}
+
+fn counter() -> Machine { //counts @1 down from 3, keeping it in :2 0
+    machine(&[
+        SetRV(1, 3),
+        SaveDIR(0, 1, 2),
+        SubRV(1, 1),
+        SetRR(1, 0),
+        JnzDRI(1, 3, 0),
+        End(0),
+    ])
+}
+
+#[test]
+fn step_back_undoes_everything() {
+    let mut vm = counter();
+    vm.run_for(3).unwrap();
+    let (registers, flags, pc, cycles) = (vm.registers().to_vec(), vm.flags(), vm.prgcount(), vm.cycles());
+    vm.step().unwrap();
+    vm.step().unwrap();
+    assert!(vm.step_back());
+    assert!(vm.step_back());
+    assert_eq!((vm.registers(), vm.flags(), vm.prgcount(), vm.cycles(), vm.steps()), (&registers[..], flags, pc, cycles, 3));
+    assert_eq!(vm.disc(2).unwrap()[0], 3);
+}
+
+#[test]
+fn step_back_after_the_end() {
+    let mut vm = counter();
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert!(vm.step_back());
+    assert!(vm.is_active());
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+}
+
+#[test]
+fn rewind_to_a_step() {
+    let mut vm = counter();
+    vm.run().unwrap();
+    let end = vm.steps();
+    assert!(vm.rewind_to(2));
+    assert_eq!((vm.steps(), vm.prgcount(), vm.registers()[1]), (2, 6, 3));
+    assert!(!vm.rewind_to(end)); //the journal only goes backwards
+    assert!(vm.rewind_to(0));
+    assert_eq!((vm.prgcount(), vm.registers()[1], vm.disc(2).unwrap()[0]), (0, 0, 0));
+    assert!(!vm.step_back());
+}
+
+#[test]
+fn rewind_to_a_cycle() {
+    let mut vm = counter();
+    vm.run_for(1).unwrap();
+    let (steps, cycles) = (vm.steps(), vm.cycles());
+    vm.run().unwrap();
+    assert!(!vm.rewind_to_cycle(vm.cycles() + 1));
+    assert!(vm.rewind_to_cycle(cycles));
+    assert_eq!((vm.steps(), vm.cycles()), (steps, cycles));
+    vm.step().unwrap();
+    let after = vm.cycles();
+    assert!(after > cycles + 1); //SAVE costs more than a cycle, so this lands in the middle of it
+    assert!(vm.rewind_to_cycle(after - 1));
+    assert_eq!((vm.steps(), vm.cycles()), (steps, cycles)); //back to before it started
+    assert!(vm.rewind_to_cycle(0));
+    assert_eq!((vm.steps(), vm.prgcount()), (0, 0));
+}
+
+#[test]
+fn limited_journal() {
+    let mut vm = counter();
+    vm.set_journal_limit(4);
+    vm.run().unwrap();
+    assert_eq!(vm.journal_len(), 4);
+    assert_eq!(vm.oldest_step(), vm.steps() - 4);
+    assert!(!vm.rewind_to(vm.steps() - 5));
+    assert!(vm.rewind_to(vm.steps() - 4));
+    assert_eq!(vm.oldest_cycle(), vm.cycles());
+    assert!(!vm.rewind_to_cycle(vm.cycles() - 1));
+}
+
+#[test]
+fn back_to_last_write() {
+    let mut vm = counter();
+    vm.run().unwrap();
+    assert!(vm.back_to_memory_write(2, 0));
+    assert_eq!((vm.prgcount(), vm.registers()[1]), (3, 1));
+    assert!(vm.back_to_register_write(1));
+    assert_eq!((vm.prgcount(), vm.registers()[1]), (9, 2));
+    assert!(!vm.back_to_register_write(7));
+}
+
+#[test]
+fn failed_fetches_are_not_journaled() {
+    let mut vm = machine(&[JmpDI(255, 0)]);
+    vm.step().unwrap();
+    for _ in 0..3 {
+        assert!(vm.step().is_err());
+    }
+    assert_eq!((vm.journal_len(), vm.oldest_step(), vm.steps()), (1, 0, 1));
+    assert!(!vm.rewind_to(9));
+    assert!(vm.rewind_to(0));
+    assert_eq!(vm.prgcount(), 0);
+}
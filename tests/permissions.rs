@@ -0,0 +1,44 @@
+mod common;
+
+use common::{program, put};
+use risc_vm::{Access, Fault, Machine, Permissions, Stop};
+use risc_vm::Instruction::*;
+
+fn machine(code: &[risc_vm::Instruction]) -> Machine {
+    let mut vm = Machine::new();
+    vm.load_program(&[program(code)]).unwrap();
+    vm
+}
+
+#[test]
+fn code_is_read_only() {
+    let mut vm = machine(&[LoadDIR(0, 1, 0), SaveDIV(200, 1, 0), End(0)]);
+    vm.set_permissions(0, Permissions::CODE).unwrap();
+    let err = vm.run().unwrap_err();
+    assert_eq!((err.fault, err.pc), (Fault::AccessDenied { disc: 0, index: 200, access: Access::Write }, 3));
+    assert_eq!(vm.registers()[1], program(&[LoadDIR(0, 1, 0)])[0]); //reading it was fine
+    assert_eq!(vm.disc(0).unwrap()[200], 0);
+}
+
+#[test]
+fn unreadable_and_unrunnable() {
+    let mut vm = machine(&[LoadDIR(0, 1, 2), End(0)]);
+    vm.set_permissions(2, Permissions { read: false, write: true, execute: true }).unwrap();
+    assert_eq!(vm.run().unwrap_err().fault, Fault::AccessDenied { disc: 2, index: 0, access: Access::Read });
+    let mut vm = machine(&[JmpDI(0, 1)]);
+    vm.set_permissions(1, Permissions::DATA).unwrap();
+    let err = vm.run().unwrap_err();
+    assert_eq!((err.fault, err.disc, err.pc), (Fault::AccessDenied { disc: 1, index: 0, access: Access::Execute }, 1, 0));
+}
+
+#[test]
+fn denied_access_can_be_trapped() {
+    let mut disc = program(&[TrapDI(60, 0), SaveDIV(5, 1, 0), End(0)]);
+    put(&mut disc, 60, SetRR(5, 0));
+    put(&mut disc, 63, Rett);
+    let mut vm = Machine::new();
+    vm.load_program(&[disc]).unwrap();
+    vm.set_permissions(0, Permissions::CODE).unwrap();
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert_eq!(vm.registers()[5], 9);
+}
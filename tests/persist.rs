@@ -0,0 +1,80 @@
+mod common;
+
+use std::{env, fs, io, path::PathBuf, process::Command};
+use risc_vm::{Machine, FLUSH_INDEX};
+use risc_vm::Instruction::*;
+
+fn path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("risc_vm_persist_{}_{}", std::process::id(), name));
+    fs::remove_file(&path).ok();
+    path
+}
+
+fn machine(program: &[risc_vm::Instruction]) -> Machine {
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(program)]).unwrap();
+    vm
+}
+
+#[test]
+fn backed_disc_loads_from_its_file() {
+    let file = path("load");
+    let mut vm = Machine::new();
+    vm.back_disc(7, &file).unwrap(); //no file yet, so the disc stays as it was
+    assert_eq!(vm.disc(7).unwrap()[..], [0; 256][..]);
+    fs::write(&file, [1, 2, 3]).unwrap();
+    vm.back_disc(7, &file).unwrap();
+    assert_eq!(vm.disc(7).unwrap()[..4], [1, 2, 3, 0]);
+    assert_eq!(vm.backed_discs(), [(7, file.clone())]);
+    fs::write(&file, [0; 257]).unwrap();
+    assert_eq!(vm.back_disc(7, &file).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert_eq!(vm.back_disc(9, &file).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    fs::remove_file(file).ok();
+}
+
+#[test]
+fn program_flushes_through_the_flush_index() {
+    let file = path("flush");
+    let mut vm = machine(&[SaveDIV(0, 42, 7), SaveDIV(FLUSH_INDEX, 1, 7), LoadDIR(FLUSH_INDEX, 3, 7), End(0)]);
+    vm.back_disc(7, &file).unwrap();
+    vm.run_for(1).unwrap();
+    assert!(!file.exists()); //nothing saved until asked
+    vm.run_for(2).unwrap();
+    let saved = fs::read(&file).unwrap();
+    assert_eq!((saved.len(), saved[0], saved[FLUSH_INDEX as usize]), (256, 42, 0));
+    assert_eq!(vm.registers()[3], 0); //the flush byte reads back as 0
+    fs::remove_file(file).ok();
+}
+
+#[test]
+fn flush_interval() {
+    let file = path("interval");
+    let mut vm = machine(&[SaveDIV(0, 1, 7), SaveDIV(0, 2, 7), SaveDIV(0, 3, 7), End(0)]);
+    vm.back_disc(7, &file).unwrap();
+    vm.set_flush_interval(Some(2));
+    vm.run_for(1).unwrap();
+    assert!(!file.exists());
+    vm.run_for(1).unwrap();
+    assert_eq!(fs::read(&file).unwrap()[0], 2);
+    vm.run_for(1).unwrap();
+    assert_eq!(fs::read(&file).unwrap()[0], 2); //not until step 4
+    vm.run_for(1).unwrap();
+    assert_eq!(fs::read(&file).unwrap()[0], 3);
+    fs::remove_file(file).ok();
+}
+
+#[test]
+fn survives_between_runs() {
+    let (src, disc) = (path("count.rasm"), path("count.disc"));
+    let rex = src.with_extension("rex");
+    fs::write(&src, "LOAD :7 0 @1\nADD @1 1\nSAVE :7 0 @0\nEND\n").unwrap();
+    let rvm = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_risc_vm")).args(args).status().unwrap().code();
+    assert_eq!(rvm(&["compile", src.to_str().unwrap(), rex.to_str().unwrap()]), Some(0));
+    let persist = format!("7={}", disc.display());
+    assert_eq!(rvm(&["run", rex.to_str().unwrap(), "--persist", &persist]), Some(0));
+    assert_eq!(rvm(&["run", rex.to_str().unwrap(), "--persist", &persist, "--flush-every", "1"]), Some(0));
+    assert_eq!(fs::read(&disc).unwrap()[0], 2);
+    for p in [src, rex, disc] {
+        fs::remove_file(p).ok();
+    }
+}
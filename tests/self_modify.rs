@@ -0,0 +1,73 @@
+mod common;
+
+use risc_vm::{Instruction, Machine, SelfModify, Stop};
+use risc_vm::Instruction::*;
+
+fn machine(program: &[Instruction]) -> Machine {
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(program)]).unwrap();
+    vm.set_self_modify(SelfModify::Report);
+    vm
+}
+
+#[test]
+fn repeated_writes_are_reported_once() {
+    let mut vm = machine(&[
+        SetRV(1, 50),
+        SaveDIV(0, 9, 0), //over the SET, fifty times
+        SubRV(1, 1),
+        SetRR(1, 0),
+        JnzDRI(1, 3, 0),
+        SaveDIV(1, 9, 0), //another byte of it, once
+        End(0),
+    ]);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    let writes: Vec<_> = vm.code_writes().iter().map(|w| (w.writer, w.target, w.instruction)).collect();
+    assert_eq!(writes, [((0, 3), (0, 0), (0, 0)), ((0, 15), (0, 1), (0, 0))]);
+}
+
+fn at_breakpoint() -> Machine { //has run the SET, and is about to write over it
+    let mut disc = common::program(&[SetRV(1, 1), JmpDI(30, 0)]);
+    common::put(&mut disc, 30, SaveDIV(1, 9, 0));
+    common::put(&mut disc, 33, End(0));
+    let mut vm = Machine::new();
+    vm.load_program(&[disc]).unwrap();
+    vm.set_self_modify(SelfModify::Report);
+    vm.add_breakpoint(0, 30);
+    assert_eq!(vm.run().unwrap(), Stop::Breakpoint { disc: 0, pc: 30 });
+    vm
+}
+
+#[test]
+fn host_writes_forget_what_ran() {
+    let mut vm = at_breakpoint();
+    vm.run().unwrap();
+    assert_eq!(vm.code_writes().len(), 1);
+    let mut vm = at_breakpoint();
+    vm.poke(0, 1, 1).unwrap();
+    vm.run().unwrap();
+    assert!(vm.code_writes().is_empty());
+    let mut vm = at_breakpoint();
+    let disc = *vm.disc(0).unwrap();
+    vm.load_disc(0, disc).unwrap();
+    vm.run().unwrap();
+    assert!(vm.code_writes().is_empty());
+    let mut vm = at_breakpoint();
+    vm.load_program(&[disc]).unwrap();
+    vm.run().unwrap();
+    assert!(vm.code_writes().is_empty());
+}
+
+#[test]
+fn restore_forgets_what_ran() {
+    let mut vm = at_breakpoint();
+    let mut snapshot = vec![];
+    vm.save_state(&mut snapshot).unwrap();
+    vm.run().unwrap();
+    assert_eq!(vm.code_writes().len(), 1);
+    vm.restore_state(&mut &snapshot[..]).unwrap();
+    assert!(vm.code_writes().is_empty());
+    vm.remove_breakpoint(0, 30);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert!(vm.code_writes().is_empty());
+}
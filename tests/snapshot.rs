@@ -0,0 +1,93 @@
+mod common;
+
+use common::program;
+use risc_vm::{Machine, Permissions, SnapshotError, Stop};
+use risc_vm::Instruction::*;
+
+fn snapshot(vm: &Machine) -> Vec<u8> {
+    let mut out = vec![];
+    vm.save_state(&mut out).unwrap();
+    out
+}
+
+fn midway() -> Machine { //a machine with a bit of everything in its state
+    let mut vm = Machine::new();
+    vm.load_program(&[program(&[
+        SetRV(1, 5),
+        Push(1),
+        AddRV(1, 255),
+        TrapDI(30, 0),
+        SaveDIV(9, 7, 2),
+        End(3),
+    ])]).unwrap();
+    vm.set_permissions(3, Permissions::DATA).unwrap();
+    assert_eq!(vm.run_for(4).unwrap(), None);
+    vm
+}
+
+#[test]
+fn round_trip() {
+    let mut vm = midway();
+    let saved = snapshot(&vm);
+    let mut copy = Machine::new();
+    copy.restore_state(&mut &saved[..]).unwrap();
+    assert_eq!(snapshot(&copy), saved);
+    assert_eq!((copy.registers(), copy.flags(), copy.sp(), copy.trap_vector()), (vm.registers(), vm.flags(), vm.sp(), Some((0, 30))));
+    assert_eq!((copy.dsccount(), copy.prgcount(), copy.steps(), copy.cycles()), (0, 12, 4, 5));
+    assert_eq!(copy.permissions(3), Some(Permissions::DATA));
+    assert_eq!(vm.run().unwrap(), Stop::Halted(3));
+    assert_eq!(copy.run().unwrap(), Stop::Halted(3));
+    assert_eq!(snapshot(&copy), snapshot(&vm));
+}
+
+#[test]
+fn version_one() { //before flags, the stack, traps, status and permissions
+    let mut old = b"RSNP".to_vec();
+    old.extend_from_slice(&[1, 8, 8]);
+    let mut discs = vec![0; 8*256];
+    discs[256+4] = 42;
+    old.extend_from_slice(&discs);
+    old.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    old.extend_from_slice(&[9, 1, 1, 0]);
+    old.extend_from_slice(&10u64.to_le_bytes());
+    old.extend_from_slice(&20u64.to_le_bytes());
+    let mut vm = Machine::new();
+    vm.restore_state(&mut &old[..]).unwrap();
+    assert_eq!((vm.dsccount(), vm.prgcount(), vm.steps(), vm.cycles()), (1, 9, 10, 20));
+    assert_eq!(vm.registers(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(vm.disc(1).unwrap()[4], 42);
+    assert_eq!((vm.flags(), vm.sp(), vm.stack_disc(), vm.trap_vector()), (0, 0, 6, None));
+    assert_eq!(vm.permissions(0), Some(Permissions::ALL));
+}
+
+#[test]
+fn bad_snapshot_leaves_machine_untouched() {
+    let good = snapshot(&midway());
+    let corrupt = |at: usize, v: u8| {
+        let mut s = good.clone();
+        s[at] = v;
+        s
+    };
+    let state = 4 + 3 + 8*256 + 8; //where prgcount, dsccount, isactive and jmp start
+    let bad = [
+        corrupt(0, b'X'), //magic
+        corrupt(4, 200), //version
+        corrupt(5, 7), //disc count
+        corrupt(state + 1, 9), //current disc
+        corrupt(state + 6, 8), //stack disc
+        good[..good.len() - 1].to_vec(), //truncated
+    ];
+    for s in bad.iter() {
+        let mut vm = Machine::new();
+        vm.load_program(&[program(&[SetRV(2, 9), End(0)])]).unwrap();
+        vm.step().unwrap();
+        let before = snapshot(&vm);
+        assert!(vm.restore_state(&mut &s[..]).is_err());
+        assert_eq!(snapshot(&vm), before);
+    }
+    let mut vm = Machine::new();
+    match vm.restore_state(&mut &corrupt(5, 7)[..]) {
+        Err(SnapshotError::Geometry { discs: 7, registers: 8 }) => {},
+        x => panic!("{:?}", x),
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{program, put};
+use risc_vm::{Fault, Machine, Stop};
+use risc_vm::Instruction::*;
+
+#[test]
+fn call_and_return_across_discs() {
+    let mut main = program(&[
+        SetRV(2, 30),
+        CallDR(2, 0),
+        CallDI(0, 1),
+        End(0),
+    ]);
+    for (i, ins) in [AddRV(1, 10), SetRR(1, 0), Ret].iter().enumerate() {
+        put(&mut main, 30 + i*3, *ins);
+    }
+    let other = program(&[CallDI(30, 0), AddRV(1, 100), SetRR(1, 0), Ret]);
+    let mut vm = Machine::new();
+    vm.load_program(&[main, other]).unwrap();
+    vm.add_breakpoint(0, 36); //the RET at :0 30
+    assert_eq!(vm.run().unwrap(), Stop::Breakpoint { disc: 0, pc: 36 });
+    assert_eq!((vm.sp(), &vm.disc(6).unwrap()[..2]), (2, &[6, 0][..])); //index, then disc
+    assert_eq!(vm.run().unwrap(), Stop::Breakpoint { disc: 0, pc: 36 });
+    assert_eq!((vm.sp(), &vm.disc(6).unwrap()[..4]), (4, &[9, 0, 3, 1][..]));
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert_eq!((vm.registers()[1], vm.sp()), (120, 0));
+}
+
+#[test]
+fn stack_overflow() {
+    let mut vm = Machine::new();
+    vm.load_program(&[program(&[Push(1), JmpDI(0, 0)])]).unwrap();
+    let err = vm.run().unwrap_err();
+    assert_eq!((err.fault, err.pc, vm.sp()), (Fault::StackOverflow, 0, 255));
+}
+
+#[test]
+fn return_without_call() {
+    let mut vm = Machine::new();
+    vm.load_program(&[program(&[Ret])]).unwrap();
+    assert_eq!(vm.run().unwrap_err().fault, Fault::StackUnderflow);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::{cell::RefCell, env, fs, process::Command, rc::Rc};
+use risc_vm::{Fault, Machine, MemoryWrite, RegisterWrite, Stop, TraceEvent, Tracer};
+use risc_vm::trace::{Csv, JsonLines};
+use risc_vm::Instruction::*;
+
+struct Events(Rc<RefCell<Vec<TraceEvent>>>);
+
+impl Tracer for Events {
+    fn trace(&mut self, event: &TraceEvent) -> Result<(), Fault> {
+        self.0.borrow_mut().push(event.clone());
+        Ok(())
+    }
+}
+
+fn events() -> Vec<TraceEvent> { //SET @1 5, SAVE it to :2 10, END
+    let mut vm = Machine::new();
+    vm.load_program(&[common::program(&[SetRV(1, 5), SaveDIR(10, 1, 2), End(0)])]).unwrap();
+    let events = Rc::new(RefCell::new(vec![]));
+    vm.set_tracer(Some(Box::new(Events(events.clone()))));
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    let events = events.borrow().clone();
+    events
+}
+
+#[test]
+fn event_fields() {
+    let e = events();
+    assert_eq!(e.len(), 3);
+    assert_eq!((e[0].step, e[0].disc, e[0].pc, e[0].instruction), (1, 0, 0, Some(SetRV(1, 5))));
+    assert_eq!(e[0].registers, [RegisterWrite { register: 1, old: 0, new: 5 }]);
+    assert!(e[0].memory.is_empty());
+    assert_eq!((e[1].step, e[1].pc, e[1].ins), (2, 3, risc_vm::encode(SaveDIR(10, 1, 2))));
+    assert_eq!(e[1].memory, [MemoryWrite { disc: 2, index: 10, old: 0, new: 5 }]);
+    assert!(e[1].registers.is_empty());
+    assert_eq!((e[2].step, e[2].pc, e[2].mnemonic()), (3, 6, String::from("END")));
+}
+
+#[test]
+fn json_lines() {
+    let mut out = vec![];
+    let mut tracer = JsonLines::new(&mut out);
+    for e in events() {
+        tracer.trace(&e).unwrap();
+    }
+    assert_eq!(String::from_utf8(out).unwrap(), JSON);
+}
+
+#[test]
+fn csv() {
+    let mut out = vec![];
+    let mut tracer = Csv::new(&mut out);
+    for e in events() {
+        tracer.trace(&e).unwrap();
+    }
+    assert_eq!(String::from_utf8(out).unwrap(), CSV);
+}
+
+#[test]
+fn trace_option() { //picks the format from the extension
+    let dir = env::temp_dir();
+    let src = dir.join(format!("risc_vm_trace_{}.rasm", std::process::id()));
+    let rex = src.with_extension("rex");
+    fs::write(&src, "SET @1 5\nSAVE :2 10 @1\nEND\n").unwrap();
+    let rvm = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_risc_vm")).args(args).status().unwrap().code();
+    assert_eq!(rvm(&["compile", src.to_str().unwrap(), rex.to_str().unwrap()]), Some(0));
+    for (extension, expected) in [("jsonl", JSON), ("csv", CSV)] {
+        let trace = src.with_extension(extension);
+        assert_eq!(rvm(&["run", rex.to_str().unwrap(), "--trace", trace.to_str().unwrap()]), Some(0));
+        assert_eq!(fs::read_to_string(&trace).unwrap(), expected);
+        fs::remove_file(trace).ok();
+    }
+    fs::remove_file(src).ok();
+    fs::remove_file(rex).ok();
+}
+
+const JSON: &str = r#"{"step":1,"disc":0,"pc":0,"bytes":[129,1,5],"ins":"SET @1 5","registers":[{"register":1,"old":0,"new":5}],"memory":[]}
+{"step":2,"disc":0,"pc":3,"bytes":[18,10,1],"ins":"SAVE :2 10 @1","registers":[],"memory":[{"disc":2,"index":10,"old":0,"new":5}]}
+{"step":3,"disc":0,"pc":6,"bytes":[0,0,0],"ins":"END","registers":[],"memory":[]}
+"#;
+const CSV: &str = "step,disc,pc,bytes,ins,registers,memory
+1,0,0,10000001 00000001 00000101,SET @1 5,@1=0>5,
+2,0,3,00010010 00001010 00000001,SAVE :2 10 @1,,:2 10=0>5
+3,0,6,00000000 00000000 00000000,END,,
+";
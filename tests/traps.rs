@@ -0,0 +1,119 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+use common::put;
+use risc_vm::{Fault, Instruction, Machine, Stop, TraceEvent, Tracer};
+use risc_vm::Instruction::*;
+
+const HANDLER: u8 = 60; //where the programs below keep their fault handler
+
+fn machine(program: &[Instruction], handler: &[Instruction]) -> Machine {
+    let mut disc = common::program(program);
+    for (i, ins) in handler.iter().enumerate() {
+        put(&mut disc, HANDLER as usize + i*3, *ins);
+    }
+    let mut vm = Machine::new();
+    vm.load_program(&[disc]).unwrap();
+    vm
+}
+
+#[test]
+fn handled_fault_carries_on() {
+    let mut vm = machine(&[
+        TrapDI(HANDLER, 0),
+        SetRV(0, 77), //something the fault interrupts
+        SetRV(2, 0),
+        DivRR(1, 2),
+        SetRR(3, 0),
+        End(0),
+    ], &[
+        SetRR(4, 0),
+        Rett,
+    ]);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert_eq!(vm.registers()[4], 7); //the cause code
+    assert_eq!(vm.registers()[3], 77); //RETT put @0 back
+    assert_eq!(vm.trap_frame(), None);
+}
+
+#[test]
+fn trap_frame_while_handling() {
+    let mut vm = machine(&[TrapDI(HANDLER, 0), SetRV(0, 5), Pop(1), End(0)], &[End(9)]);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(9));
+    let frame = vm.trap_frame().unwrap();
+    assert_eq!((frame.disc, frame.pc, frame.saved), (0, 6, 5));
+    assert_eq!(vm.registers()[0], 6); //stack underflow
+}
+
+#[test]
+fn cause_codes() {
+    for (faulting, code) in [(SetRR(1, 9), 1), (JmpDI(0, 9), 2), (Pop(1), 6), (DivRV(1, 0), 7)] {
+        let mut vm = machine(&[TrapDI(HANDLER, 0), faulting, End(0)], &[SetRR(5, 0), Rett]);
+        assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+        assert_eq!(vm.registers()[5], code, "{}", faulting);
+    }
+}
+
+#[test]
+fn handler_can_be_used_again() {
+    let mut vm = machine(&[
+        TrapDI(HANDLER, 0),
+        DivRV(1, 0),
+        DivRV(1, 0),
+        End(0),
+    ], &[
+        AddRV(6, 1),
+        SetRR(6, 0),
+        Rett,
+    ]);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(0));
+    assert_eq!(vm.registers()[6], 2);
+}
+
+#[test]
+fn fault_inside_handler_stops_the_machine() {
+    let mut vm = machine(&[TrapDI(HANDLER, 0), DivRV(1, 0), End(0)], &[Pop(1), Rett]);
+    let err = vm.run().unwrap_err();
+    assert_eq!((err.fault, err.pc), (Fault::StackUnderflow, HANDLER));
+    assert_eq!(vm.trap_frame().map(|f| f.pc), Some(3));
+}
+
+#[test]
+fn rett_outside_handler() {
+    let mut vm = machine(&[Rett], &[]);
+    assert_eq!(vm.run().unwrap_err().fault, Fault::IllegalInstruction(0b11110000));
+}
+
+#[test]
+fn without_a_vector_faults_stop_the_machine() {
+    let mut vm = machine(&[DivRV(1, 0), End(0)], &[End(1)]);
+    assert_eq!(vm.run().unwrap_err().fault, Fault::DivideByZero);
+}
+
+struct Steps(Rc<RefCell<Vec<TraceEvent>>>);
+
+impl Tracer for Steps {
+    fn trace(&mut self, event: &TraceEvent) -> Result<(), Fault> {
+        self.0.borrow_mut().push(event.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn trapped_fetch_is_not_traced() {
+    let mut vm = machine(&[TrapDI(HANDLER, 0), JmpDI(254, 0)], &[End(9)]);
+    let steps = Rc::new(RefCell::new(vec![]));
+    vm.set_tracer(Some(Box::new(Steps(steps.clone()))));
+    assert_eq!(vm.run().unwrap(), Stop::Halted(9));
+    let steps: Vec<_> = steps.borrow().iter().map(|e| (e.step, e.pc, e.instruction)).collect();
+    assert_eq!(steps, [(1, 0, Some(TrapDI(HANDLER, 0))), (2, 3, Some(JmpDI(254, 0))), (3, HANDLER, Some(End(9)))]);
+}
+
+#[test]
+fn handler_can_find_the_fault() {
+    let mut vm = machine(&[TrapDI(HANDLER, 0), SetRV(1, 4), DivRV(1, 0), End(0)], &[TlocRR(6, 7), End(9)]);
+    assert_eq!(vm.run().unwrap(), Stop::Halted(9));
+    assert_eq!((vm.registers()[6], vm.registers()[7]), (0, 6));
+    let mut vm = machine(&[TlocRR(6, 7)], &[]);
+    assert_eq!(vm.run().unwrap_err().fault, Fault::IllegalInstruction(0b11110010)); //not handling anything
+}